@@ -0,0 +1,349 @@
+use std::{collections::HashMap, error::Error, fmt, fs, path::Path};
+
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+
+/// How serious an advisory is, from its CVSS score
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Advisories without a CVSS score, such as unmaintained crates
+    Unknown,
+    None,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    fn from_score(score: f64) -> Self {
+        match score {
+            s if s >= 9.0 => Severity::Critical,
+            s if s >= 7.0 => Severity::High,
+            s if s >= 4.0 => Severity::Medium,
+            s if s > 0.0 => Severity::Low,
+            _ => Severity::None,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Unknown => f.write_str("unknown"),
+            Severity::None => f.write_str("none"),
+            Severity::Low => f.write_str("low"),
+            Severity::Medium => f.write_str("medium"),
+            Severity::High => f.write_str("high"),
+            Severity::Critical => f.write_str("critical"),
+        }
+    }
+}
+
+/// The front matter of an advisory file
+#[derive(Deserialize)]
+struct AdvisoryFile {
+    advisory: AdvisoryMeta,
+    #[serde(default)]
+    versions: AdvisoryVersions,
+}
+
+#[derive(Deserialize)]
+struct AdvisoryMeta {
+    id: String,
+    package: String,
+    /// Only in the older `.toml` advisories.  Newer ones give it as the Markdown heading.
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    cvss: Option<String>,
+    /// The kind of informational advisory, e.g. `unmaintained`
+    #[serde(default)]
+    informational: Option<String>,
+    #[serde(default)]
+    withdrawn: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+struct AdvisoryVersions {
+    #[serde(default)]
+    patched: Vec<String>,
+    #[serde(default)]
+    unaffected: Vec<String>,
+}
+
+/// A RustSec security advisory for a crate
+#[derive(Debug)]
+pub struct Advisory {
+    pub id: String,
+    pub package: String,
+    pub title: String,
+    pub severity: Severity,
+    /// The kind of informational advisory, e.g. `unmaintained`
+    pub informational: Option<String>,
+    /// The version requirements that have the fix, as written in the advisory
+    pub patched: Vec<String>,
+    patched_reqs: Vec<VersionReq>,
+    unaffected_reqs: Vec<VersionReq>,
+}
+
+impl Advisory {
+    fn parse(path: &Path) -> Result<Option<Self>, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)?;
+        let (front, body) = if path.extension().is_some_and(|ext| ext == "md") {
+            // the TOML front matter is in a fenced code block at the top of the file
+            let start = contents.find("```toml").ok_or("missing front matter")? + "```toml".len();
+            let len = contents[start..]
+                .find("```")
+                .ok_or("unterminated front matter")?;
+            (&contents[start..start + len], &contents[start + len + 3..])
+        } else {
+            (contents.as_str(), "")
+        };
+
+        let file: AdvisoryFile = toml::from_str(front)?;
+        if file.advisory.withdrawn.is_some() {
+            return Ok(None);
+        }
+        let title = file
+            .advisory
+            .title
+            .or_else(|| {
+                body.lines()
+                    .find_map(|line| line.strip_prefix("# "))
+                    .map(|title| title.trim().to_string())
+            })
+            .unwrap_or_default();
+        // only CVSS v3 vectors are scored
+        let severity = file
+            .advisory
+            .cvss
+            .as_deref()
+            .and_then(cvss_score)
+            .map_or(Severity::Unknown, Severity::from_score);
+        let reqs = |reqs: &[String]| {
+            reqs.iter()
+                .map(|req| VersionReq::parse(req))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(Some(Self {
+            id: file.advisory.id,
+            package: file.advisory.package,
+            title,
+            severity,
+            informational: file.advisory.informational,
+            patched_reqs: reqs(&file.versions.patched)?,
+            unaffected_reqs: reqs(&file.versions.unaffected)?,
+            patched: file.versions.patched,
+        }))
+    }
+
+    /// The kind of informational advisory, or the severity of a vulnerability
+    pub fn kind(&self) -> String {
+        match &self.informational {
+            Some(kind) => kind.clone(),
+            None => self.severity.to_string(),
+        }
+    }
+
+    /// Whether the version of the crate is vulnerable
+    pub fn affects(&self, version: &str) -> bool {
+        match Version::parse(version) {
+            Ok(version) => !self
+                .patched_reqs
+                .iter()
+                .chain(self.unaffected_reqs.iter())
+                .any(|req| req.matches(&version)),
+            Err(_) => false,
+        }
+    }
+}
+
+/// The advisories of a local clone of the RustSec advisory database
+pub struct AdvisoryDb {
+    advisories: HashMap<String, Vec<Advisory>>,
+}
+
+impl AdvisoryDb {
+    /// Reads every advisory in the database's `crates` directory.  Withdrawn advisories and any
+    /// that can't be parsed are skipped.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let crates = path.join("crates");
+        let dirs = fs::read_dir(&crates).map_err(|err| {
+            format!(
+                "{} is not a RustSec advisory database: {}",
+                path.display(),
+                err
+            )
+        })?;
+
+        let mut advisories: HashMap<String, Vec<Advisory>> = HashMap::new();
+        for dir in dirs.filter_map(Result::ok) {
+            let files = fs::read_dir(dir.path())
+                .into_iter()
+                .flatten()
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| is_advisory(path));
+            for file in files {
+                if let Ok(Some(advisory)) = Advisory::parse(&file) {
+                    advisories
+                        .entry(advisory.package.clone())
+                        .or_default()
+                        .push(advisory);
+                }
+            }
+        }
+        for list in advisories.values_mut() {
+            list.sort_by(|a, b| b.id.cmp(&a.id));
+        }
+
+        Ok(Self { advisories })
+    }
+
+    /// All advisories for the crate, newest first
+    pub fn for_crate(&self, name: &str) -> &[Advisory] {
+        self.advisories.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// The advisories affecting the version of the crate
+    pub fn affecting(&self, name: &str, version: &str) -> Vec<&Advisory> {
+        self.for_crate(name)
+            .iter()
+            .filter(|advisory| advisory.affects(version))
+            .collect()
+    }
+}
+
+fn is_advisory(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    name.starts_with("RUSTSEC-") && (name.ends_with(".md") || name.ends_with(".toml"))
+}
+
+/// The base score of a CVSS v3 vector such as `CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H`
+fn cvss_score(vector: &str) -> Option<f64> {
+    if !vector.starts_with("CVSS:3.") {
+        return None;
+    }
+    let metrics = vector
+        .split('/')
+        .skip(1)
+        .filter_map(|metric| metric.split_once(':'))
+        .collect::<HashMap<_, _>>();
+    let changed = *metrics.get("S")? == "C";
+
+    let av = match *metrics.get("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        "P" => 0.2,
+        _ => return None,
+    };
+    let ac = match *metrics.get("AC")? {
+        "L" => 0.77,
+        "H" => 0.44,
+        _ => return None,
+    };
+    let pr = match (*metrics.get("PR")?, changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        ("H", false) => 0.27,
+        ("H", true) => 0.5,
+        _ => return None,
+    };
+    let ui = match *metrics.get("UI")? {
+        "N" => 0.85,
+        "R" => 0.62,
+        _ => return None,
+    };
+    let cia = |key: &str| match *metrics.get(key)? {
+        "H" => Some(0.56),
+        "L" => Some(0.22),
+        "N" => Some(0.0),
+        _ => None,
+    };
+
+    let iss = 1.0 - (1.0 - cia("C")?) * (1.0 - cia("I")?) * (1.0 - cia("A")?);
+    let impact = if changed {
+        7.52 * (iss - 0.029) - 3.25 * (iss - 0.02f64).powi(15)
+    } else {
+        6.42 * iss
+    };
+    let exploitability = 8.22 * av * ac * pr * ui;
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+
+    let score = if changed {
+        1.08 * (impact + exploitability)
+    } else {
+        impact + exploitability
+    };
+    Some(round_up(score.min(10.0)))
+}
+
+/// Rounds up to one decimal place the way CVSS 3.1 does, so that floating point errors such as
+/// 4.000000001 don't round up to 4.1
+fn round_up(score: f64) -> f64 {
+    let score = (score * 100_000.0).round() as u64;
+    if score.is_multiple_of(10_000) {
+        score as f64 / 100_000.0
+    } else {
+        (score / 10_000 + 1) as f64 / 10.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cvss_scores() {
+        let scores = [
+            ("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H", 9.8),
+            ("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H", 10.0),
+            ("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:H", 7.5),
+            ("CVSS:3.0/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N", 6.1),
+            ("CVSS:3.1/AV:N/AC:L/PR:L/UI:N/S:C/C:L/I:L/A:N", 6.4),
+            ("CVSS:3.1/AV:L/AC:L/PR:L/UI:N/S:U/C:H/I:H/A:H", 7.8),
+            ("CVSS:3.1/AV:N/AC:H/PR:N/UI:N/S:U/C:H/I:N/A:N", 5.9),
+            ("CVSS:3.1/AV:A/AC:H/PR:H/UI:R/S:C/C:N/I:L/A:H", 5.9),
+            ("CVSS:3.1/AV:P/AC:H/PR:H/UI:R/S:U/C:L/I:N/A:N", 1.6),
+            ("CVSS:3.1/AV:L/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:N", 0.0),
+        ];
+        for (vector, score) in scores {
+            assert_eq!(cvss_score(vector), Some(score), "{}", vector);
+        }
+    }
+
+    #[test]
+    fn round_up_to_one_decimal() {
+        assert_eq!(round_up(4.02), 4.1);
+        assert_eq!(round_up(4.000_000_001), 4.0);
+        assert_eq!(round_up(4.0), 4.0);
+    }
+
+    #[test]
+    fn unscored_vectors() {
+        assert_eq!(cvss_score("AV:N/AC:L/Au:N/C:P/I:P/A:P"), None);
+        assert_eq!(cvss_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H"), None);
+        assert_eq!(
+            cvss_score("CVSS:3.1/AV:X/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"),
+            None
+        );
+    }
+
+    #[test]
+    fn severities() {
+        assert_eq!(Severity::from_score(9.8), Severity::Critical);
+        assert_eq!(Severity::from_score(7.0), Severity::High);
+        assert_eq!(Severity::from_score(6.9), Severity::Medium);
+        assert_eq!(Severity::from_score(0.1), Severity::Low);
+        assert_eq!(Severity::from_score(0.0), Severity::None);
+    }
+}
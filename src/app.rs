@@ -1,11 +1,21 @@
 use std::collections::VecDeque;
 use std::error::Error;
-use std::{cmp, sync::mpsc::Receiver, time::Duration};
+use std::{
+    cmp,
+    sync::mpsc::{Receiver, RecvTimeoutError},
+    time::Duration,
+};
+use std::{
+    env,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
 use tui::{
     backend::Backend,
-    layout::{Constraint, Layout},
-    text::Text,
-    widgets::{Block, BorderType, Borders, Paragraph},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Spans, Text},
+    widgets::{Block, BorderType, Borders, Paragraph, Tabs, Wrap},
     Frame,
 };
 
@@ -16,12 +26,39 @@ use crate::toast::ToastState;
 use crate::{crates_io::CrateSearch, toast::ToastMessage};
 
 use crate::{
+    advisory::{Advisory, AdvisoryDb},
     ceil_div,
-    crates_io::{CrateSearcher, CratesSort},
+    config::{ClipboardBackend, Config},
+    crates_io::{CopyFormat, CrateLink, CrateSearcher, CratesSort, SearchQuery, CRATES_IO_API},
+    filter::{FilterOption, SearchFilter},
+    history::SearchHistory,
     input::InputEvent,
-    widgets::{CrateWidget, InputWidget, SortingWidget},
+    keymap::Action,
+    license,
+    manifest::{DepTable, DiffLine, Manifest},
+    nav::{NavEntry, NavStack, View},
+    osc52,
+    outdated::{outdated_report, OutdatedEntry},
+    project::Project,
+    readme::readme_to_text,
+    theme::theme,
+    widgets::{
+        CategoriesWidget, CategoryRow, CompareColumn, CompareWidget, CrateDetailsWidget,
+        CrateWidget, DependenciesWidget, DiffWidget, HistoryWidget, InputWidget, KeywordsWidget,
+        MenuWidget, OutdatedWidget, OwnersWidget, ReadmeWidget, SortingWidget, VersionsWidget,
+    },
 };
 
+/// The most crates that can be compared side by side
+const MAX_COMPARE: usize = 4;
+
+/// The smallest terminal the TUI is drawn in.  Smaller ones show a message instead.
+const MIN_WIDTH: u16 = 40;
+const MIN_HEIGHT: u16 = 12;
+
+/// The most search results fetched at once while filling a page of filtered results
+const MAX_FETCH_AHEAD: u32 = 500;
+
 pub struct SortingField {
     pub(crate) selection: usize,
     pub(crate) items: Vec<CratesSort>,
@@ -30,12 +67,13 @@ pub struct SortingField {
 
 impl From<&'_ CratesSort> for SortingField {
     fn from(sort: &'_ CratesSort) -> Self {
-        let mut items = Vec::with_capacity(4);
-        items.push(CratesSort::Relevance);
-        items.push(CratesSort::AllTimeDownload);
-        items.push(CratesSort::RecentDownload);
-        items.push(CratesSort::RecentUpdate);
-        items.push(CratesSort::NewlyAdded);
+        let items = vec![
+            CratesSort::Relevance,
+            CratesSort::AllTimeDownload,
+            CratesSort::RecentDownload,
+            CratesSort::RecentUpdate,
+            CratesSort::NewlyAdded,
+        ];
 
         let selection = items.iter().position(|item| sort.eq(item)).unwrap();
         let strs = items.iter().map(|item| format!("{}", item)).collect();
@@ -48,44 +86,355 @@ impl From<&'_ CratesSort> for SortingField {
     }
 }
 
+#[derive(Default)]
+pub struct InputField {
+    pub(crate) text: String,
+    /// The history entry currently recalled with Up/Down, if any
+    pub(crate) history_pos: Option<usize>,
+    /// The text that was typed before stepping into the history
+    pub(crate) draft: String,
+}
+
+impl InputField {
+    /// Steps to an older search in the history
+    fn recall_prev(&mut self, history: &SearchHistory) {
+        let pos = self.history_pos.map_or(0, |pos| pos + 1);
+        if let Some(entry) = history.get(pos) {
+            if self.history_pos.is_none() {
+                self.draft = std::mem::take(&mut self.text);
+            }
+            self.history_pos = Some(pos);
+            self.text = entry.term.clone();
+        }
+    }
+
+    /// Steps to a newer search in the history, restoring the typed text after the newest one
+    fn recall_next(&mut self, history: &SearchHistory) {
+        match self.history_pos {
+            Some(0) => {
+                self.history_pos = None;
+                self.text = std::mem::take(&mut self.draft);
+            }
+            Some(pos) => {
+                if let Some(entry) = history.get(pos - 1) {
+                    self.history_pos = Some(pos - 1);
+                    self.text = entry.term.clone();
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Any edit detaches the input from the recalled history entry
+    fn edit(&mut self) -> &mut String {
+        self.history_pos = None;
+        &mut self.text
+    }
+}
+
+/// Editing the tab's filters.  They are applied to the tab once confirmed.
+pub struct FilterField {
+    pub(crate) selection: usize,
+    pub(crate) filter: SearchFilter,
+}
+
+impl FilterField {
+    fn strs(&self) -> Vec<String> {
+        FilterOption::ALL
+            .iter()
+            .map(|option| self.filter.describe(*option))
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub struct HistoryField {
+    /// The fuzzy filter typed by the user
+    pub(crate) filter: String,
+    /// The selected index into the filtered entries
+    pub(crate) selection: usize,
+}
+
+/// A popup menu of choices, shown using the strings in `strs`
+pub struct MenuField<T> {
+    pub(crate) selection: usize,
+    pub(crate) items: Vec<T>,
+    pub(crate) strs: Vec<String>,
+}
+
+impl<T> MenuField<T> {
+    fn next(&mut self) {
+        self.selection = cmp::min(self.selection + 1, self.items.len().saturating_sub(1));
+    }
+
+    fn prev(&mut self) {
+        self.selection = self.selection.saturating_sub(1);
+    }
+}
+
+/// The steps of adding a crate to a manifest, each with its choices
+pub enum AddStep {
+    Manifest(MenuField<PathBuf>),
+    Table(MenuField<DepTable>),
+    Version(MenuField<String>),
+    /// The features of the chosen version and whether each is enabled
+    Features(MenuField<(String, bool)>),
+    /// The changes to the manifest and the first line of them shown
+    Preview(Vec<DiffLine>, usize),
+}
+
+impl AddStep {
+    fn next(&mut self) {
+        match self {
+            AddStep::Manifest(menu) => menu.next(),
+            AddStep::Table(menu) => menu.next(),
+            AddStep::Version(menu) => menu.next(),
+            AddStep::Features(menu) => menu.next(),
+            AddStep::Preview(lines, scroll) => {
+                *scroll = cmp::min(*scroll + 1, lines.len().saturating_sub(1))
+            }
+        }
+    }
+
+    fn prev(&mut self) {
+        match self {
+            AddStep::Manifest(menu) => menu.prev(),
+            AddStep::Table(menu) => menu.prev(),
+            AddStep::Version(menu) => menu.prev(),
+            AddStep::Features(menu) => menu.prev(),
+            AddStep::Preview(_, scroll) => *scroll = scroll.saturating_sub(1),
+        }
+    }
+}
+
+/// Adding the selected crate to a manifest, choosing the manifest, table, version and features
+pub struct AddField {
+    /// the name of the crate being added
+    name: String,
+    manifest: Option<Manifest>,
+    table: Option<DepTable>,
+    version: Option<String>,
+    step: AddStep,
+}
+
 pub enum AppMode {
     Normal,
-    Input(String),
+    Input(InputField),
     Sorting(SortingField),
+    History(HistoryField),
+    /// Choosing which of the crate's links to open
+    Open(MenuField<(CrateLink, String)>),
+    /// Choosing what to copy to the clipboard, with the text for each format
+    Copy(MenuField<(CopyFormat, String)>),
+    /// Adding the selected crate to a Cargo.toml
+    Add(Box<AddField>),
+    /// Choosing the filters applied to the search results
+    Filter(FilterField),
+}
+
+/// An independent search session.  All tabs share the app's searcher and its cache.
+pub struct Tab {
+    query: SearchQuery,
+    page: u32,
+    sort: CratesSort,
+    selection: Option<usize>,
+    /// the screen currently shown in the main area
+    view: View,
+    /// back and forward history between searches and views
+    nav: NavStack,
+    /// the filters applied to the search results on top of the query
+    filter: SearchFilter,
+}
+
+impl Default for Tab {
+    fn default() -> Self {
+        Self {
+            query: SearchQuery::default(),
+            page: 1,
+            sort: CratesSort::Relevance,
+            selection: None,
+            view: View::Search,
+            nav: NavStack::default(),
+            filter: SearchFilter::default(),
+        }
+    }
+}
+
+impl Tab {
+    /// The text shown for this tab in the tab bar
+    fn title(&self) -> String {
+        let term = self.query.to_string();
+        let term = if term.is_empty() { "New Search" } else { &term };
+        match &self.view {
+            View::Search => term.to_string(),
+            View::Details(name)
+            | View::Dependencies(name, _)
+            | View::Owners(name)
+            | View::Readme(name, _)
+            | View::Versions(name) => format!("{} > {}", term, name),
+            View::Compare(_) => format!("{} > compare", term),
+            View::Outdated => format!("{} > outdated", term),
+            View::Categories => format!("{} > categories", term),
+            View::Keywords => format!("{} > keywords", term),
+        }
+    }
+}
+
+/// What the TUI starts with
+pub struct StartState {
+    /// A search to run straight away, rather than asking for one
+    pub search: Option<String>,
+    pub sort: CratesSort,
+    pub page: u32,
+    pub items_per_page: u32,
 }
 
 pub struct App {
     input_rx: Receiver<InputEvent>,
     client: CrateSearcher,
     pub quit: bool,
-    inpt: Option<String>,
-    page: u32,
     items_per_page: u32,
-    sort: CratesSort,
     mode: AppMode,
-    selection: Option<usize>,
     /// a queue of toast messages to display to the user
     toast: VecDeque<ToastState>,
+    /// previously submitted searches
+    history: SearchHistory,
+    /// the open search sessions
+    tabs: Vec<Tab>,
+    /// the index of the active tab
+    tab: usize,
+    /// the names of the crates marked for comparison
+    marked: Vec<String>,
+    /// the size of the main area when it was last drawn, used to scroll the README
+    view_size: (u16, u16),
+    /// the link that was last opened, selected first the next time
+    last_link: CrateLink,
+    config: Config,
+    /// the Cargo project in the working directory, if there is one
+    project: Option<Project>,
+    /// the outdated report for the project, made when it is first viewed
+    outdated: Option<Vec<OutdatedEntry>>,
+    /// the local RustSec advisory database, if one is configured
+    advisories: Option<AdvisoryDb>,
+    /// the slugs of the categories whose subcategories are shown
+    expanded: Vec<String>,
 }
 
 impl App {
-    pub fn new(input_rx: Receiver<InputEvent>) -> Self {
-        Self {
+    pub fn new(input_rx: Receiver<InputEvent>, config: Config, start: StartState) -> Self {
+        let mut toast = VecDeque::new();
+        let advisories =
+            config
+                .advisory_db
+                .as_deref()
+                .and_then(|path| match AdvisoryDb::load(path) {
+                    Ok(db) => Some(db),
+                    Err(err) => {
+                        toast.push_back(ToastState::err(
+                            Some("Advisory Database".to_string()),
+                            format!("{}", err),
+                        ));
+                        None
+                    }
+                });
+
+        let api = config.api().unwrap_or(CRATES_IO_API);
+        let mut app = Self {
             input_rx,
-            client: CrateSearcher::new().unwrap(),
+            client: CrateSearcher::new(api, config.cache_ttl()).unwrap(),
             quit: false,
-            inpt: Some("".to_string()),
-            page: 1,
-            items_per_page: 5,
-            mode: AppMode::Input("".to_string()),
-            sort: CratesSort::Relevance,
-            selection: None,
-            toast: VecDeque::new(),
+            items_per_page: start.items_per_page,
+            // the search is typed in for a query that can't be parsed, so it can be fixed
+            mode: AppMode::Input(InputField {
+                text: start.search.clone().unwrap_or_default(),
+                ..InputField::default()
+            }),
+            toast,
+            history: SearchHistory::load(),
+            project: env::current_dir()
+                .ok()
+                .and_then(|dir| Project::detect(&dir)),
+            outdated: None,
+            advisories,
+            expanded: Vec::new(),
+            tabs: vec![Tab {
+                sort: start.sort.clone(),
+                ..Tab::default()
+            }],
+            tab: 0,
+            marked: Vec::new(),
+            view_size: (0, 0),
+            last_link: CrateLink::Documentation,
+            config,
+        };
+        if let Some(search) = start.search {
+            app.run_search(search, start.sort);
+            if start.page > 1 && matches!(app.mode, AppMode::Normal) {
+                app.tab_mut().page = start.page;
+                app.do_search();
+            }
         }
+        app
+    }
+
+    /// The advisories for the crate.  Empty without an advisory database.
+    fn advisories(&self, name: &str) -> &[Advisory] {
+        self.advisories
+            .as_ref()
+            .map_or(&[], |db| db.for_crate(name))
+    }
+
+    /// Warns when the version of the crate is affected by an advisory
+    fn warn_vulnerable(&mut self, name: &str, version: &str) {
+        let warning = self.advisories.as_ref().and_then(|db| {
+            let affecting = db.affecting(name, version);
+            let advisory = affecting.first()?;
+            let mut message = format!(
+                "{} {} is affected by {}: {}",
+                name, version, advisory.id, advisory.title
+            );
+            if affecting.len() > 1 {
+                message.push_str(&format!(" and {} more", affecting.len() - 1));
+            }
+            Some(message)
+        });
+        if let Some(message) = warning {
+            self.toast.push_back(ToastState::warn(
+                Some("Vulnerable version".to_string()),
+                message,
+            ));
+        }
+    }
+
+    fn tab(&self) -> &Tab {
+        &self.tabs[self.tab]
+    }
+
+    fn tab_mut(&mut self) -> &mut Tab {
+        &mut self.tabs[self.tab]
     }
 
     pub fn draw<T: Backend>(&mut self, f: &mut Frame<T>) {
         let size = f.size();
+        if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+            let message = format!(
+                "Terminal too small\n\n{}x{} is needed, this is {}x{}.\nMake the window larger to continue.",
+                MIN_WIDTH, MIN_HEIGHT, size.width, size.height
+            );
+            let y = size.height.saturating_sub(4) / 2;
+            let area = Rect {
+                y,
+                height: size.height - y,
+                ..size
+            };
+            f.render_widget(
+                Paragraph::new(message)
+                    .alignment(Alignment::Center)
+                    .wrap(Wrap { trim: true }),
+                area,
+            );
+            return;
+        }
         let block = Block::default()
             .title("Cratuity (A crates.io quick search TUI)")
             .borders(Borders::ALL)
@@ -95,21 +444,87 @@ impl App {
 
         // render the top message
         let splits = Layout::default()
-            .constraints([Constraint::Length(2), Constraint::Min(5)].as_ref())
+            .constraints(
+                [
+                    Constraint::Length(2),
+                    Constraint::Length(1),
+                    Constraint::Min(5),
+                ]
+                .as_ref(),
+            )
             .split(area);
 
         let top = splits[0];
-        let area = splits[1];
-        let message = match self.mode {
-            AppMode::Normal => {
-                Text::raw("Press N/P to move between pages.  Press f to search for a term\nPress J/K to change the highlighted Crate and press C to copy it's Cargo.toml string") 
+        let area = splits[2];
+
+        // render the tab bar
+        let titles = self
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(i, tab)| Spans::from(format!("{}: {}", i + 1, tab.title())))
+            .collect();
+        let tabs = Tabs::new(titles).select(self.tab).highlight_style(
+            Style::default()
+                .fg(theme().highlight.0)
+                .add_modifier(Modifier::BOLD),
+        );
+        f.render_widget(tabs, splits[1]);
+
+        let message = match (&self.mode, &self.tab().view) {
+            (AppMode::Normal, View::Search) => {
+                Text::raw("Press N/P to change pages, F to search, E to filter and B to browse.  Press Enter to view the Crate's details\nPress J/K to change the highlighted Crate, C to copy, O to open, A to add to Cargo.toml, M to mark or V to compare") 
+            }
+            (AppMode::Normal, View::Details(_)) => {
+                "Press D for dependencies, W for owners, L for versions and R for the README.  Press C to copy and O to open links\nPress Backspace or Alt-Left to go back and Alt-Right to go forward".into()
+            }
+            (AppMode::Normal, View::Readme(..)) => {
+                "Press J/K to scroll and N/P to scroll by a page\nPress Backspace or Alt-Left to go back and Alt-Right to go forward".into()
+            }
+            (AppMode::Normal, View::Dependencies(..)) => {
+                "Press J/K to change the highlighted dependency and Enter to view its details\nPress Backspace or Alt-Left to go back and Alt-Right to go forward".into()
+            }
+            (AppMode::Normal, View::Compare(_)) => {
+                "Press M on search results or details to mark Crates for comparison\nPress Backspace or Alt-Left to go back and Alt-Right to go forward".into()
+            }
+            (AppMode::Normal, View::Versions(_)) => {
+                "Press J/K to change the highlighted version, D to view its dependencies and R to read its README\nPress Backspace or Alt-Left to go back and Alt-Right to go forward".into()
             }
-            AppMode::Input(_) => {
-                "Type to enter your search term.  Press Enter to confirm.  Press ESC to cancel".into()
+            (AppMode::Normal, View::Outdated) => {
+                "Press J/K to change the highlighted dependency, Enter to view its details and L to view its versions\nPress Backspace or Alt-Left to go back and Alt-Right to go forward".into()
             }
-            AppMode::Sorting(_) => {
+            (AppMode::Normal, View::Categories) => {
+                "Press J/K to change the highlighted category, Space to show its subcategories and Enter to list its crates\nPress B to browse keywords instead.  Press Backspace or Alt-Left to go back and Alt-Right to go forward".into()
+            }
+            (AppMode::Normal, View::Keywords) => {
+                "Press J/K to change the highlighted keyword and Enter to list its crates\nPress B to browse categories instead.  Press Backspace or Alt-Left to go back and Alt-Right to go forward".into()
+            }
+            (AppMode::Normal, View::Owners(_)) => {
+                "Press J/K to change the highlighted owner\nPress Backspace or Alt-Left to go back and Alt-Right to go forward".into()
+            }
+            (AppMode::Input(_), _) => {
+                "Type to enter your search term.  Press Enter to confirm.  Press ESC to cancel\nPress Up/Down to recall previous searches.  Press Ctrl-R to search the history".into()
+            }
+            (AppMode::Sorting(_), _) => {
                 "Press J/K to move between options.  Press Enter to confirm.  Press ESC to cancel".into()
             }
+            (AppMode::Add(field), _) => match field.step {
+                AddStep::Features(_) => "Press J/K to move between features and Space to enable or disable them.  Press Enter to confirm.  Press ESC to cancel".into(),
+                AddStep::Preview(..) => "Press J/K to scroll.  Press Enter or Y to save the changes.  Press ESC to cancel".into(),
+                _ => "Press J/K to move between options.  Press Enter to confirm.  Press ESC to cancel".into(),
+            },
+            (AppMode::Open(_), _) => {
+                "Press J/K to move between links.  Press Enter or the link's number to open it.  Press ESC to cancel".into()
+            }
+            (AppMode::Copy(_), _) => {
+                "Press J/K to move between formats.  Press Enter or the format's number to copy it.  Press ESC to cancel".into()
+            }
+            (AppMode::Filter(_), _) => {
+                "Press J/K to move between filters and H/L or Space to change them.  Press R to reset them all\nPress Enter to apply the filters.  Press ESC to cancel".into()
+            }
+            (AppMode::History(_), _) => {
+                "Type to filter previous searches.  Press Up/Down to select.  Press Enter to search again.  Press ESC to cancel".into()
+            }
         };
         let message = Paragraph::new(message);
         f.render_widget(message, top);
@@ -122,53 +537,212 @@ impl App {
         let bot = splits[1];
         let area = splits[0];
 
+        let keymap = &self.config.keymap;
+        let hint = format!(
+            "{}: new tab  Tab: next tab  {}: close tab",
+            keymap.key_for(Action::NewTab),
+            keymap.key_for(Action::CloseTab)
+        );
+        let splits = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Min(0),
+                    Constraint::Length(hint.chars().count() as u16),
+                ]
+                .as_ref(),
+            )
+            .split(bot);
+        let bot = splits[0];
+        f.render_widget(Paragraph::new(hint), splits[1]);
+
+        // the other views are drawn with the same margin as the search results
+        let view_area = Layout::default()
+            .horizontal_margin(1)
+            .constraints([Constraint::Min(0)].as_ref())
+            .split(area)[0];
+        self.view_size = (view_area.width, view_area.height);
+
+        match &self.tab().view {
+            View::Search => self.draw_search(f, area, bot),
+            View::Details(name) => {
+                if let Some(crte) = self.client.get_crate_cached(name) {
+                    f.render_widget(CrateDetailsWidget::new(crte), view_area);
+                }
+                f.render_widget(Paragraph::new(name.as_str()), bot);
+            }
+            View::Dependencies(name, version) => {
+                if let Some(deps) = self.client.get_dependencies_cached(name, version) {
+                    f.render_widget(
+                        DependenciesWidget::new(deps, self.tab().selection),
+                        view_area,
+                    );
+                }
+                let message = format!("{} {} > Dependencies", name, version);
+                f.render_widget(Paragraph::new(message), bot);
+            }
+            View::Compare(names) => {
+                let columns = names
+                    .iter()
+                    .filter_map(|name| {
+                        let crte = self.client.get_crate_cached(name)?;
+                        Some(CompareColumn {
+                            crte,
+                            owners: self.client.get_owners_cached(name).map(Vec::as_slice),
+                            deps: self
+                                .client
+                                .get_dependencies_cached(name, &crte.crte.newest_version)
+                                .map(Vec::as_slice),
+                        })
+                    })
+                    .collect();
+                f.render_widget(CompareWidget::new(columns), view_area);
+                let message = format!("Comparing {}", names.join(", "));
+                f.render_widget(Paragraph::new(message), bot);
+            }
+            View::Readme(name, version) => {
+                if let Some(readme) = self.client.get_readme_cached(name, version) {
+                    let title = format!("README: {} {}", name, version);
+                    let scroll = self.tab().selection.unwrap_or(0);
+                    let widget = ReadmeWidget::new(readme_to_text(readme), &title, scroll);
+                    f.render_widget(widget, view_area);
+                }
+                let message = format!("{} {} > README", name, version);
+                f.render_widget(Paragraph::new(message), bot);
+            }
+            View::Versions(name) => {
+                if let Some(crte) = self.client.get_crate_cached(name) {
+                    let widget = VersionsWidget::new(crte, self.tab().selection)
+                        .advisories(self.advisories(name));
+                    f.render_widget(widget, view_area);
+                }
+                let message = format!("{} > Versions", name);
+                f.render_widget(Paragraph::new(message), bot);
+            }
+            View::Outdated => {
+                if let Some(entries) = &self.outdated {
+                    f.render_widget(
+                        OutdatedWidget::new(entries, self.tab().selection),
+                        view_area,
+                    );
+                    let outdated = entries.iter().filter(|entry| entry.is_outdated()).count();
+                    let message =
+                        format!("{} of {} dependencies outdated", outdated, entries.len());
+                    f.render_widget(Paragraph::new(message), bot);
+                }
+            }
+            View::Categories => {
+                let rows = self.category_rows();
+                f.render_widget(
+                    CategoriesWidget::new(&rows, self.tab().selection),
+                    view_area,
+                );
+                let count = self.client.get_categories_cached().map_or(0, Vec::len);
+                let message = format!("{} categories", count);
+                f.render_widget(Paragraph::new(message), bot);
+            }
+            View::Keywords => {
+                if let Some(keywords) = self.client.get_keywords_cached() {
+                    f.render_widget(
+                        KeywordsWidget::new(keywords, self.tab().selection),
+                        view_area,
+                    );
+                    let message = format!("The {} most used keywords", keywords.len());
+                    f.render_widget(Paragraph::new(message), bot);
+                }
+            }
+            View::Owners(name) => {
+                if let Some(owners) = self.client.get_owners_cached(name) {
+                    f.render_widget(OwnersWidget::new(owners, self.tab().selection), view_area);
+                }
+                let message = format!("{} > Owners", name);
+                f.render_widget(Paragraph::new(message), bot);
+            }
+        }
+
+        f.render_widget(block, size);
+        self.draw_mode(f);
+
+        if let Some(toast) = self.toast.front_mut() {
+            f.render_stateful_widget(ToastMessage {}, size, toast);
+        }
+    }
+
+    fn draw_search<T: Backend>(&self, f: &mut Frame<T>, area: Rect, bot: Rect) {
         if let Some((total, crates)) = self.get_cached_crates() {
-            let message =
-                Paragraph::new(format!("Page {} of {}", self.page, self.num_pages(total)));
-            f.render_widget(message, bot);
+            let pages = self.num_pages(total).max(1);
+            let filtered = if self.is_filtering() {
+                self.filtered_crates()
+            } else {
+                None
+            };
+            let message = match filtered {
+                Some((total, scanned, matched)) if scanned < total => format!(
+                    "Page {} of at most {}  ({} matches in {} of {} results scanned)",
+                    self.tab().page,
+                    pages,
+                    matched.len(),
+                    scanned,
+                    total
+                ),
+                Some((total, _, matched)) => format!(
+                    "Page {} of {}  ({} of {} results match the filters)",
+                    self.tab().page,
+                    pages,
+                    matched.len(),
+                    total
+                ),
+                _ => format!("Page {} of {}", self.tab().page, pages),
+            };
+            f.render_widget(Paragraph::new(message), bot);
 
             let mut widgets = Vec::new();
             for (i, crte) in crates.iter().enumerate() {
-                if let Some(selection) = self.selection {
-                    widgets.push(CrateWidget::new(crte, selection == i));
+                let marked = self.marked.contains(&crte.name);
+                let project = self
+                    .project
+                    .as_ref()
+                    .and_then(|project| project.get(&crte.name));
+                let advisories = self.advisories(&crte.name);
+                let license = self.license(crte);
+                if let Some(selection) = self.tab().selection {
+                    widgets.push(
+                        CrateWidget::new(crte, selection == i)
+                            .marked(marked)
+                            .project(project)
+                            .advisories(advisories)
+                            .license(license),
+                    );
                 } else {
-                    widgets.push(CrateWidget::new(crte, false));
+                    widgets.push(
+                        CrateWidget::new(crte, false)
+                            .marked(marked)
+                            .project(project)
+                            .advisories(advisories)
+                            .license(license),
+                    );
                 }
             }
 
-            let mut raw = vec![100u16 / (self.items_per_page as u16); self.items_per_page as usize];
-            let sum_diff = 100 - raw.iter().sum::<u16>();
-            if sum_diff != 0 {
-                for item in raw[..sum_diff as usize].iter_mut() {
-                    *item += 1;
-                }
-            }
+            let items_per_page = self.items_per_page.max(1);
             let splits = Layout::default()
                 .horizontal_margin(1)
-                .constraints(
-                    raw.into_iter()
-                        .map(Constraint::Percentage)
-                        .collect::<Vec<_>>(),
-                )
+                .constraints(vec![
+                    Constraint::Ratio(1, items_per_page);
+                    items_per_page as usize
+                ])
                 .split(area);
             widgets
                 .into_iter()
                 .zip(splits)
                 .for_each(|(w, a)| f.render_widget(w, a));
         }
-
-        f.render_widget(block, size);
-        self.draw_mode(f);
-
-        self.toast
-            .front_mut()
-            .map(|toast| f.render_stateful_widget(ToastMessage {}, size, toast));
     }
 
     fn draw_mode<T: Backend>(&self, f: &mut Frame<T>) {
         match &self.mode {
-            AppMode::Input(msg) => {
-                let inpt = InputWidget::new("Enter your search term", msg.as_str());
+            AppMode::Input(field) => {
+                let inpt = InputWidget::new("Enter your search term", field.text.as_str());
                 f.render_widget(inpt, f.size());
             }
             AppMode::Normal => {}
@@ -176,58 +750,169 @@ impl App {
                 let widget = SortingWidget::new(state, "Select your sorting method");
                 f.render_widget(widget, f.size());
             }
+            AppMode::Open(state) => {
+                let widget =
+                    MenuWidget::new(state.strs.clone(), state.selection, "Open in browser");
+                f.render_widget(widget, f.size());
+            }
+            AppMode::Copy(state) => {
+                let widget =
+                    MenuWidget::new(state.strs.clone(), state.selection, "Copy to clipboard");
+                f.render_widget(widget, f.size());
+            }
+            AppMode::Filter(field) => {
+                let widget = MenuWidget::new(field.strs(), field.selection, "Filter results");
+                f.render_widget(widget, f.size());
+            }
+            AppMode::History(state) => {
+                let entries = self.history.fuzzy_filter(&state.filter);
+                let widget = HistoryWidget::new(state, entries, "Search history");
+                f.render_widget(widget, f.size());
+            }
+            AppMode::Add(field) => {
+                let (strs, selection, title) = match &field.step {
+                    AddStep::Manifest(menu) => {
+                        (&menu.strs, menu.selection, "Add to which manifest?")
+                    }
+                    AddStep::Table(menu) => (&menu.strs, menu.selection, "Add to which table?"),
+                    AddStep::Version(menu) => (&menu.strs, menu.selection, "Which version?"),
+                    AddStep::Features(menu) => (&menu.strs, menu.selection, "Which features?"),
+                    AddStep::Preview(lines, scroll) => {
+                        let title = match &field.manifest {
+                            Some(manifest) => {
+                                format!("Add {} to {}?", field.name, manifest.path.display())
+                            }
+                            None => format!("Add {}?", field.name),
+                        };
+                        f.render_widget(DiffWidget::new(lines, &title, *scroll), f.size());
+                        return;
+                    }
+                };
+                let widget = MenuWidget::new(strs.clone(), selection, title);
+                f.render_widget(widget, f.size());
+            }
         }
     }
 
     fn next_item(&mut self) {
-        if let Some(selection) = self.selection {
+        if self.tab().view != View::Search {
+            let len = self.list_len();
+            self.tab_mut().selection = match self.tab().selection {
+                Some(selection) => Some(cmp::min(selection + 1, len.saturating_sub(1))),
+                None if len > 0 => Some(0),
+                None => None,
+            };
+            return;
+        }
+
+        if let Some(selection) = self.tab().selection {
             if let Some((_, crates)) = self.get_cached_crates() {
                 if selection + 1 >= crates.len() {
                     self.next_page();
                 } else {
-                    self.selection = Some(selection + 1);
+                    self.tab_mut().selection = Some(selection + 1);
                 }
             }
         }
     }
 
     fn prev_item(&mut self) {
-        if let Some(selection) = self.selection {
-            self.selection = if selection == 0 && self.page != 1 {
-                self.prev_page();
-                if let Some((_, crates)) = self.get_cached_crates() {
-                    Some(crates.len().saturating_sub(1))
+        if let Some(selection) = self.tab().selection {
+            self.tab_mut().selection =
+                if selection == 0 && self.tab().page != 1 && self.tab().view == View::Search {
+                    self.prev_page();
+                    if let Some((_, crates)) = self.get_cached_crates() {
+                        Some(crates.len().saturating_sub(1))
+                    } else {
+                        None
+                    }
                 } else {
-                    None
+                    Some(selection.saturating_sub(1))
                 }
-            } else {
-                Some(selection.saturating_sub(1))
+        }
+    }
+
+    /// The number of rows in the list shown by the current view
+    fn list_len(&self) -> usize {
+        match &self.tab().view {
+            View::Dependencies(name, version) => self
+                .client
+                .get_dependencies_cached(name, version)
+                .map_or(0, Vec::len),
+            View::Owners(name) => self.client.get_owners_cached(name).map_or(0, Vec::len),
+            View::Versions(name) => self
+                .client
+                .get_crate_cached(name)
+                .map_or(0, |crte| crte.versions.len()),
+            View::Outdated => self.outdated.as_ref().map_or(0, Vec::len),
+            View::Categories => self.category_rows().len(),
+            View::Keywords => self.client.get_keywords_cached().map_or(0, Vec::len),
+            View::Readme(name, version) => {
+                // estimate the wrapped height inside the README's borders
+                let width = self.view_size.0.saturating_sub(2).max(1) as usize;
+                self.client
+                    .get_readme_cached(name, version)
+                    .map_or(0, |readme| {
+                        readme_to_text(readme)
+                            .lines
+                            .iter()
+                            .map(|line| ceil_div(line.width() as u32, width as u32).max(1) as usize)
+                            .sum()
+                    })
             }
+            View::Search | View::Details(_) | View::Compare(_) => 0,
         }
     }
 
+    /// Scrolls the README by a page in the given direction
+    fn scroll_page(&mut self, down: bool) {
+        let page = self.view_size.1.saturating_sub(3).max(1) as usize;
+        let max = self.list_len().saturating_sub(1);
+        let selection = self.tab().selection.unwrap_or(0);
+        self.tab_mut().selection = Some(if down {
+            cmp::min(selection + page, max)
+        } else {
+            selection.saturating_sub(page)
+        });
+    }
+
     fn next_page(&mut self) {
+        if let View::Readme(..) = self.tab().view {
+            self.scroll_page(true);
+            return;
+        }
+        if self.tab().view != View::Search {
+            return;
+        }
         if let Some((total, _)) = self.get_cached_crates() {
-            if self.page * self.items_per_page < total {
-                self.selection = Some(0);
-                self.page += 1;
+            if self.tab().page * self.items_per_page < total {
+                self.tab_mut().selection = Some(0);
+                self.tab_mut().page += 1;
                 self.do_search();
             }
         }
     }
 
     fn prev_page(&mut self) {
-        if self.page > 1 {
-            self.selection = Some(0);
-            self.page -= 1;
+        if let View::Readme(..) = self.tab().view {
+            self.scroll_page(false);
+            return;
+        }
+        if self.tab().page > 1 && self.tab().view == View::Search {
+            self.tab_mut().selection = Some(0);
+            self.tab_mut().page -= 1;
             self.do_search();
         }
     }
 
     fn home(&mut self) {
-        self.selection = Some(0);
-        if self.page != 1 {
-            self.page = 1;
+        if self.tab().view != View::Search {
+            self.tab_mut().selection = if self.list_len() > 0 { Some(0) } else { None };
+            return;
+        }
+        self.tab_mut().selection = Some(0);
+        if self.tab().page != 1 {
+            self.tab_mut().page = 1;
             self.do_search();
         }
     }
@@ -237,44 +922,88 @@ impl App {
     }
 
     fn end(&mut self) {
+        if self.tab().view != View::Search {
+            self.tab_mut().selection = self.list_len().checked_sub(1);
+            return;
+        }
         if let Some((total, _)) = self.get_cached_crates() {
-            if self.page * self.items_per_page < total {
-                self.page = self.num_pages(total);
+            if self.tab().page * self.items_per_page < total {
+                self.tab_mut().page = self.num_pages(total);
                 self.do_search();
             }
         }
         if let Some((_, crates)) = self.get_cached_crates() {
-            self.selection = crates.len().checked_sub(1);
+            self.tab_mut().selection = crates.len().checked_sub(1);
         }
     }
 
     pub fn await_input(&mut self) {
-        if let Ok(inpt) = self.input_rx.recv_timeout(Duration::from_secs(1)) {
+        let inpt = match self.input_rx.recv_timeout(Duration::from_secs(1)) {
+            Ok(inpt) => Some(inpt),
+            // the input thread stopped, so no more keys can be read
+            Err(RecvTimeoutError::Disconnected) => {
+                self.quit = true;
+                None
+            }
+            Err(RecvTimeoutError::Timeout) => None,
+        };
+        if let Some(inpt) = inpt {
+            // rebound keys are handled as the default keys of their actions
+            let inpt = match inpt {
+                InputEvent::Char(c) if matches!(self.mode, AppMode::Normal) => {
+                    match self.config.keymap.translate(c) {
+                        Some(c) => InputEvent::Char(c),
+                        None => return,
+                    }
+                }
+                inpt => inpt,
+            };
             match &mut self.mode {
                 AppMode::Normal => match inpt {
                     InputEvent::Char(c) => match c {
-                        'f' | 'F' => self.mode = AppMode::Input("".to_string()),
+                        'f' | 'F' => self.mode = AppMode::Input(InputField::default()),
                         'q' | 'Q' => self.quit = true,
                         'n' | 'N' => self.next_page(),
                         'p' | 'P' => self.prev_page(),
                         'j' | 'J' => self.next_item(),
                         'k' | 'K' => self.prev_item(),
-                        's' | 'S' => self.mode = AppMode::Sorting(SortingField::from(&self.sort)),
-                        'o' | 'O' => {
-                            if let Err(msg) = self.open_selection() {
-                                self.toast.push_back(ToastState::err(
-                                    Some("Cannot open in browser"),
-                                    format!("{}", msg).as_str(),
-                                ))
+                        'g' => self.home(),
+                        'G' => self.end(),
+                        's' | 'S' => {
+                            self.mode = AppMode::Sorting(SortingField::from(&self.tab().sort))
+                        }
+                        'd' | 'D' => self.view_dependencies(),
+                        'w' | 'W' => self.view_owners(),
+                        'm' | 'M' => self.toggle_mark(),
+                        'r' | 'R' => self.view_readme(),
+                        'v' | 'V' => self.view_compare(),
+                        't' | 'T' => {
+                            self.tabs.push(Tab {
+                                sort: self.config.sort.clone(),
+                                ..Tab::default()
+                            });
+                            self.tab = self.tabs.len() - 1;
+                            self.mode = AppMode::Input(InputField::default());
+                        }
+                        'x' | 'X' => self.close_tab(),
+                        '1'..='9' => {
+                            let index = c.to_digit(10).unwrap() as usize - 1;
+                            if index < self.tabs.len() {
+                                self.tab = index;
                             }
                         }
-                        'c' | 'C' => {
-                            if let Err(msg) = self.copy_selection() {
-                                self.toast.push_back(ToastState::err(
-                                    Some("Clipboard Error".to_string()),
-                                    format!("{}", msg),
-                                ))
-                            };
+                        'o' | 'O' => self.open_menu(),
+                        'c' | 'C' => self.copy_menu(),
+                        'a' | 'A' => self.add_start(),
+                        'l' | 'L' => self.view_versions(),
+                        'u' | 'U' => self.view_outdated(),
+                        'b' | 'B' => self.view_browse(),
+                        ' ' if self.tab().view == View::Categories => self.toggle_category(),
+                        'e' | 'E' if self.tab().view == View::Search => {
+                            self.mode = AppMode::Filter(FilterField {
+                                selection: 0,
+                                filter: self.tab().filter.clone(),
+                            })
                         }
                         _ => {}
                     },
@@ -296,22 +1025,148 @@ impl App {
                     InputEvent::Home => {
                         self.home();
                     }
+                    InputEvent::Enter => match self.tab().view {
+                        View::Categories | View::Keywords => self.browse_selected(),
+                        _ => self.view_details(),
+                    },
+                    InputEvent::Tab => self.tab = (self.tab + 1) % self.tabs.len(),
+                    InputEvent::BackTab => {
+                        self.tab = (self.tab + self.tabs.len() - 1) % self.tabs.len()
+                    }
+                    InputEvent::Backspace | InputEvent::AltLeft => self.go_back(),
+                    InputEvent::AltRight => self.go_forward(),
+                    InputEvent::Ctrl('r') => self.mode = AppMode::History(HistoryField::default()),
 
                     _ => {}
                 },
-                AppMode::Input(ref mut msg) => match inpt {
+                AppMode::Input(ref mut field) => match inpt {
                     InputEvent::Esc => self.mode = AppMode::Normal,
                     InputEvent::Enter => {
-                        let replaced = std::mem::take(msg);
-                        self.page = 1;
-                        self.inpt = Some(replaced);
-                        self.do_search();
-                        self.mode = AppMode::Normal;
+                        let term = field.text.clone();
+                        // a recalled entry that wasn't edited is re-run with its own sort
+                        let sort = field
+                            .history_pos
+                            .and_then(|pos| self.history.get(pos))
+                            .map(|entry| entry.sort.clone())
+                            .unwrap_or_else(|| self.tab().sort.clone());
+                        self.run_search(term, sort);
                     }
                     InputEvent::Backspace => {
-                        let _ = msg.pop();
+                        let _ = field.edit().pop();
+                    }
+                    InputEvent::Up => field.recall_prev(&self.history),
+                    InputEvent::Down => field.recall_next(&self.history),
+                    InputEvent::Ctrl('r') => self.mode = AppMode::History(HistoryField::default()),
+                    InputEvent::Char(c) => field.edit().push(c),
+                    _ => {}
+                },
+                AppMode::History(ref mut field) => match inpt {
+                    InputEvent::Esc => self.mode = AppMode::Normal,
+                    InputEvent::Enter => {
+                        let entry = self
+                            .history
+                            .fuzzy_filter(&field.filter)
+                            .get(field.selection)
+                            .map(|entry| (*entry).clone());
+                        if let Some(entry) = entry {
+                            self.run_search(entry.term, entry.sort);
+                        }
+                    }
+                    InputEvent::Down | InputEvent::Ctrl('n') | InputEvent::Ctrl('r') => {
+                        let len = self.history.fuzzy_filter(&field.filter).len();
+                        field.selection = cmp::min(field.selection + 1, len.saturating_sub(1));
+                    }
+                    InputEvent::Up | InputEvent::Ctrl('p') => {
+                        field.selection = field.selection.saturating_sub(1);
+                    }
+                    InputEvent::Backspace => {
+                        let _ = field.filter.pop();
+                        field.selection = 0;
+                    }
+                    InputEvent::Char(c) => {
+                        field.filter.push(c);
+                        field.selection = 0;
+                    }
+                    _ => {}
+                },
+                AppMode::Open(ref mut menu) => match inpt {
+                    InputEvent::Esc => self.mode = AppMode::Normal,
+                    InputEvent::Enter => {
+                        let selection = menu.selection;
+                        self.open_link(selection);
+                    }
+                    InputEvent::Down | InputEvent::Char('j') | InputEvent::Char('J') => menu.next(),
+                    InputEvent::Up | InputEvent::Char('k') | InputEvent::Char('K') => menu.prev(),
+                    InputEvent::Char(c @ '1'..='9') => {
+                        let selection = c.to_digit(10).unwrap() as usize - 1;
+                        if selection < menu.items.len() {
+                            self.open_link(selection);
+                        }
+                    }
+                    _ => {}
+                },
+                AppMode::Add(ref mut field) => match inpt {
+                    InputEvent::Esc => self.mode = AppMode::Normal,
+                    InputEvent::Enter => self.add_next(),
+                    InputEvent::Char('y') | InputEvent::Char('Y')
+                        if matches!(field.step, AddStep::Preview(..)) =>
+                    {
+                        self.add_next()
+                    }
+                    InputEvent::Char(' ') => {
+                        if let AddStep::Features(menu) = &mut field.step {
+                            if let Some((_, enabled)) = menu.items.get_mut(menu.selection) {
+                                *enabled = !*enabled;
+                            }
+                            menu.strs = Self::feature_strs(&menu.items);
+                        }
+                    }
+                    InputEvent::Down | InputEvent::Char('j') | InputEvent::Char('J') => {
+                        field.step.next()
+                    }
+                    InputEvent::Up | InputEvent::Char('k') | InputEvent::Char('K') => {
+                        field.step.prev()
+                    }
+                    _ => {}
+                },
+                AppMode::Filter(ref mut field) => match inpt {
+                    InputEvent::Esc => self.mode = AppMode::Normal,
+                    InputEvent::Enter => {
+                        let filter = field.filter.clone();
+                        self.mode = AppMode::Normal;
+                        self.apply_filter(filter);
+                    }
+                    InputEvent::Down | InputEvent::Char('j') | InputEvent::Char('J') => {
+                        field.selection = cmp::min(field.selection + 1, FilterOption::ALL.len() - 1)
+                    }
+                    InputEvent::Up | InputEvent::Char('k') | InputEvent::Char('K') => {
+                        field.selection = field.selection.saturating_sub(1)
+                    }
+                    InputEvent::Right | InputEvent::Char(' ') | InputEvent::Char('l') => {
+                        field.filter.step(FilterOption::ALL[field.selection], true)
+                    }
+                    InputEvent::Left | InputEvent::Char('h') => {
+                        field.filter.step(FilterOption::ALL[field.selection], false)
+                    }
+                    InputEvent::Char('r') | InputEvent::Char('R') => {
+                        field.filter = SearchFilter::default()
+                    }
+                    _ => {}
+                },
+                AppMode::Copy(ref mut menu) => match inpt {
+                    InputEvent::Esc => self.mode = AppMode::Normal,
+                    InputEvent::Enter => {
+                        let selection = menu.selection;
+                        self.copy_format(selection);
+                    }
+                    InputEvent::Down | InputEvent::Char('j') | InputEvent::Char('J') => menu.next(),
+                    InputEvent::Up | InputEvent::Char('k') | InputEvent::Char('K') => menu.prev(),
+                    InputEvent::Char(c @ '1'..='9') => {
+                        let selection = c.to_digit(10).unwrap() as usize - 1;
+                        if selection < menu.items.len() {
+                            self.copy_format(selection);
+                        }
                     }
-                    InputEvent::Char(c) => msg.push(c),
                     _ => {}
                 },
                 AppMode::Sorting(SortingField {
@@ -321,9 +1176,12 @@ impl App {
                 }) => match inpt {
                     InputEvent::Esc => self.mode = AppMode::Normal,
                     InputEvent::Enter => {
-                        self.sort = items[*selection].clone();
-                        self.page = 1;
+                        let sort = items[*selection].clone();
                         self.mode = AppMode::Normal;
+                        self.push_nav();
+                        self.tab_mut().view = View::Search;
+                        self.tab_mut().sort = sort;
+                        self.tab_mut().page = 1;
                         self.do_search();
                     }
                     InputEvent::Right
@@ -359,75 +1217,1115 @@ impl App {
         }
     }
 
+    /// Replaces the tab's filters, going back to the first page of the results
+    fn apply_filter(&mut self, filter: SearchFilter) {
+        if self.tab().filter != filter {
+            self.push_nav();
+            self.tab_mut().filter = filter;
+            self.tab_mut().page = 1;
+            self.do_search();
+        }
+    }
+
+    /// Searches for the text typed by the user from the first page and records it in the search
+    /// history.  A query that can't be parsed is reported and leaves the mode as it is, so the
+    /// text can be fixed.
+    fn run_search(&mut self, term: String, sort: CratesSort) {
+        let query = match term.parse::<SearchQuery>() {
+            Ok(query) => query,
+            Err(err) => {
+                self.toast
+                    .push_back(ToastState::err(Some("Query Error".to_string()), err));
+                return;
+            }
+        };
+        if let Err(err) = self.history.push(&term, &sort) {
+            self.toast.push_back(ToastState::err(
+                Some("History Error".to_string()),
+                format!("Could not save the search history.\n{}", err),
+            ));
+        }
+        self.push_nav();
+        self.tab_mut().view = View::Search;
+        self.tab_mut().page = 1;
+        self.tab_mut().sort = sort;
+        self.tab_mut().query = query;
+        self.do_search();
+        self.mode = AppMode::Normal;
+    }
+
+    /// Closes the active tab.  The last tab cannot be closed.
+    fn close_tab(&mut self) {
+        if self.tabs.len() > 1 {
+            self.tabs.remove(self.tab);
+            self.tab = self.tab.min(self.tabs.len() - 1);
+        }
+    }
+
+    /// A snapshot of the current view and search
+    fn nav_entry(&self) -> NavEntry {
+        NavEntry {
+            view: self.tab().view.clone(),
+            query: self.tab().query.clone(),
+            page: self.tab().page,
+            sort: self.tab().sort.clone(),
+            filter: self.tab().filter.clone(),
+            selection: self.tab().selection,
+        }
+    }
+
+    /// Records the current state so it can be returned to.  Nothing is recorded before the first
+    /// search since there is nothing to return to.
+    fn push_nav(&mut self) {
+        if self.tab().view != View::Search || self.get_cached_crates().is_some() {
+            let entry = self.nav_entry();
+            self.tab_mut().nav.push(entry);
+        }
+    }
+
+    fn go_back(&mut self) {
+        let current = self.nav_entry();
+        if let Some(entry) = self.tab_mut().nav.back(current) {
+            self.restore(entry);
+        }
+    }
+
+    fn go_forward(&mut self) {
+        let current = self.nav_entry();
+        if let Some(entry) = self.tab_mut().nav.forward(current) {
+            self.restore(entry);
+        }
+    }
+
+    /// Returns to a previously recorded state.  Everything that was shown before is in the
+    /// searcher's cache, so this does not normally hit the network.
+    fn restore(&mut self, entry: NavEntry) {
+        self.tab_mut().view = entry.view;
+        self.tab_mut().query = entry.query;
+        self.tab_mut().page = entry.page;
+        self.tab_mut().sort = entry.sort;
+        self.tab_mut().filter = entry.filter;
+        if let Err(err) = self.load_view() {
+            self.toast.push_back(ToastState::err(
+                Some("Request Error".to_string()),
+                format!("{}", err),
+            ));
+        }
+        self.tab_mut().selection = entry.selection;
+    }
+
+    /// Moves to a new view, recording the current one in the navigation history
+    fn navigate(&mut self, view: View) {
+        let prev = self.nav_entry();
+        self.tab_mut().view = view;
+        match self.load_view() {
+            Ok(()) => {
+                self.tab_mut().nav.push(prev);
+                self.tab_mut().selection = if self.list_len() > 0 { Some(0) } else { None };
+            }
+            Err(err) => {
+                self.tab_mut().view = prev.view;
+                self.toast.push_back(ToastState::err(
+                    Some("Request Error".to_string()),
+                    format!("{}", err),
+                ));
+            }
+        }
+    }
+
+    /// Makes sure the data for the current view is loaded, fetching it if it is not cached
+    fn load_view(&mut self) -> Result<(), reqwest::Error> {
+        // borrow the tab through the field so the client can be borrowed mutably alongside it
+        match &self.tabs[self.tab].view {
+            View::Search => {
+                self.do_search();
+            }
+            View::Details(name) => {
+                self.client.get_crate_with_cache(name)?;
+            }
+            View::Dependencies(name, version) => {
+                self.client.get_dependencies_with_cache(name, version)?;
+            }
+            View::Owners(name) => {
+                self.client.get_owners_with_cache(name)?;
+            }
+            View::Readme(name, version) => {
+                self.client.get_readme_with_cache(name, version)?;
+            }
+            View::Versions(name) => {
+                self.client.get_crate_with_cache(name)?;
+            }
+            View::Categories => {
+                self.client.get_categories_with_cache()?;
+            }
+            View::Keywords => {
+                self.client.get_keywords_with_cache()?;
+            }
+            View::Outdated => {
+                if let (None, Some(project)) = (&self.outdated, &self.project) {
+                    self.outdated = Some(outdated_report(project, &mut self.client));
+                }
+            }
+            View::Compare(names) => {
+                for name in names {
+                    let version = self
+                        .client
+                        .get_crate_with_cache(name)?
+                        .crte
+                        .newest_version
+                        .clone();
+                    self.client.get_dependencies_with_cache(name, &version)?;
+                    self.client.get_owners_with_cache(name)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Opens the details of the highlighted crate or dependency
+    fn view_details(&mut self) {
+        let name = match &self.tab().view {
+            View::Search => self.selected_crate().map(|crte| crte.name.clone()),
+            View::Dependencies(name, version) => self.tab().selection.and_then(|selection| {
+                self.client
+                    .get_dependencies_cached(name, version)?
+                    .get(selection)
+                    .map(|dep| dep.crate_id.clone())
+            }),
+            View::Outdated => self.selected_outdated().map(|entry| entry.name.clone()),
+            View::Details(_)
+            | View::Owners(_)
+            | View::Compare(_)
+            | View::Readme(..)
+            | View::Versions(_)
+            | View::Categories
+            | View::Keywords => None,
+        };
+        if let Some(name) = name {
+            self.navigate(View::Details(name));
+        }
+    }
+
+    fn view_dependencies(&mut self) {
+        let version = match &self.tab().view {
+            View::Search | View::Details(_) | View::Readme(..) | View::Outdated => {
+                self.selected_crate_version()
+            }
+            View::Versions(_) => self.selected_version(),
+            View::Dependencies(..)
+            | View::Owners(_)
+            | View::Compare(_)
+            | View::Categories
+            | View::Keywords => None,
+        };
+        if let Some((name, version)) = version {
+            self.navigate(View::Dependencies(name, version));
+        }
+    }
+
+    fn view_owners(&mut self) {
+        let name = match &self.tab().view {
+            View::Search
+            | View::Details(_)
+            | View::Readme(..)
+            | View::Versions(_)
+            | View::Outdated => self.selected_crate().map(|crte| crte.name.clone()),
+            View::Dependencies(..)
+            | View::Owners(_)
+            | View::Compare(_)
+            | View::Categories
+            | View::Keywords => None,
+        };
+        if let Some(name) = name {
+            self.navigate(View::Owners(name));
+        }
+    }
+
+    fn view_readme(&mut self) {
+        let version = match &self.tab().view {
+            View::Search | View::Details(_) | View::Outdated => self.selected_crate_version(),
+            View::Versions(_) => self.selected_version(),
+            View::Dependencies(..)
+            | View::Owners(_)
+            | View::Compare(_)
+            | View::Readme(..)
+            | View::Categories
+            | View::Keywords => None,
+        };
+        if let Some((name, version)) = version {
+            self.navigate(View::Readme(name, version));
+        }
+    }
+
+    /// The name and newest version of the selected crate
+    fn selected_crate_version(&self) -> Option<(String, String)> {
+        let crte = self.selected_crate()?;
+        Some((crte.name.clone(), crte.newest_version.clone()))
+    }
+
+    /// The name and highlighted version in the versions view
+    fn selected_version(&self) -> Option<(String, String)> {
+        match &self.tab().view {
+            View::Versions(name) => {
+                let crte = self.client.get_crate_cached(name)?;
+                let version = crte.versions.get(self.tab().selection?)?;
+                Some((name.clone(), version.num.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    fn view_versions(&mut self) {
+        let name = match &self.tab().view {
+            View::Search | View::Details(_) => self.selected_crate().map(|crte| crte.name.clone()),
+            View::Outdated => self.selected_outdated().map(|entry| entry.name.clone()),
+            View::Dependencies(..)
+            | View::Owners(_)
+            | View::Compare(_)
+            | View::Readme(..)
+            | View::Versions(_)
+            | View::Categories
+            | View::Keywords => None,
+        };
+        if let Some(name) = name {
+            self.navigate(View::Versions(name));
+        }
+    }
+
+    /// Opens the outdated report for the project in the working directory.  The report is made
+    /// once, since it looks up every dependency.
+    fn view_outdated(&mut self) {
+        if self.project.is_none() {
+            self.toast.push_back(ToastState::warn(
+                Some("Outdated".to_string()),
+                "No Cargo project found in the working directory".to_string(),
+            ));
+        } else if self.tab().view != View::Outdated {
+            self.navigate(View::Outdated);
+        }
+    }
+
+    /// Opens the list of categories, or switches between the categories and keywords
+    fn view_browse(&mut self) {
+        let view = match self.tab().view {
+            View::Categories => View::Keywords,
+            _ => View::Categories,
+        };
+        self.navigate(view);
+    }
+
+    /// The categories in the order they are listed, each followed by its subcategories when it
+    /// is expanded
+    fn category_rows(&self) -> Vec<CategoryRow<'_>> {
+        let mut rows = Vec::new();
+        for category in self.client.get_categories_cached().into_iter().flatten() {
+            let expanded = self.expanded.contains(&category.slug);
+            rows.push(CategoryRow {
+                category,
+                subcategory: false,
+                expanded,
+            });
+            if expanded {
+                let subcategories = self.client.get_subcategories_cached(&category.slug);
+                rows.extend(
+                    subcategories
+                        .into_iter()
+                        .flatten()
+                        .map(|category| CategoryRow {
+                            category,
+                            subcategory: true,
+                            expanded: false,
+                        }),
+                );
+            }
+        }
+        rows
+    }
+
+    /// Shows or hides the subcategories of the highlighted category, fetching them the first
+    /// time
+    fn toggle_category(&mut self) {
+        let rows = self.category_rows();
+        let slug = match self
+            .tab()
+            .selection
+            .and_then(|selection| rows.get(selection))
+        {
+            Some(row) if !row.subcategory => row.category.slug.clone(),
+            _ => return,
+        };
+        if let Some(pos) = self.expanded.iter().position(|expanded| *expanded == slug) {
+            self.expanded.remove(pos);
+        } else {
+            match self.client.get_subcategories_with_cache(&slug) {
+                Ok(_) => self.expanded.push(slug),
+                Err(err) => self.toast.push_back(ToastState::err(
+                    Some("Request Error".to_string()),
+                    format!("{}", err),
+                )),
+            }
+        }
+    }
+
+    /// Lists the crates in the highlighted category or with the highlighted keyword
+    fn browse_selected(&mut self) {
+        let selection = match self.tab().selection {
+            Some(selection) => selection,
+            None => return,
+        };
+        let query = match self.tab().view {
+            View::Categories => SearchQuery {
+                category: self
+                    .category_rows()
+                    .get(selection)
+                    .map(|row| row.category.slug.clone()),
+                ..SearchQuery::default()
+            },
+            View::Keywords => SearchQuery {
+                keyword: self
+                    .client
+                    .get_keywords_cached()
+                    .and_then(|keywords| keywords.get(selection))
+                    .map(|keyword| keyword.keyword.clone()),
+                ..SearchQuery::default()
+            },
+            _ => return,
+        };
+        self.push_nav();
+        self.tab_mut().view = View::Search;
+        self.tab_mut().page = 1;
+        self.tab_mut().query = query;
+        self.do_search();
+    }
+
+    /// The highlighted entry of the outdated report
+    fn selected_outdated(&self) -> Option<&OutdatedEntry> {
+        self.outdated.as_ref()?.get(self.tab().selection?)
+    }
+
+    /// The crate the user is looking at: the highlighted search result, or the crate the current
+    /// view is about.
+    fn selected_crate(&self) -> Option<&CrateSearch> {
+        match &self.tab().view {
+            View::Search => {
+                let (_, crates) = self.get_cached_crates()?;
+                crates.get(self.tab().selection?).copied()
+            }
+            View::Details(name)
+            | View::Dependencies(name, _)
+            | View::Owners(name)
+            | View::Readme(name, _)
+            | View::Versions(name) => self
+                .client
+                .get_crate_cached(name)
+                .map(|resp| &resp.crte)
+                // views opened straight from the results may not have the full crate cached
+                .or_else(|| {
+                    let (_, crates) = self.get_cached_crates()?;
+                    crates.into_iter().find(|crte| crte.name == *name)
+                }),
+            View::Outdated => {
+                let name = &self.selected_outdated()?.name;
+                self.client.get_crate_cached(name).map(|resp| &resp.crte)
+            }
+            View::Compare(_) | View::Categories | View::Keywords => None,
+        }
+    }
+
+    /// Marks or unmarks the selected crate for comparison
+    fn toggle_mark(&mut self) {
+        let name = match self.selected_crate() {
+            Some(crte) => crte.name.clone(),
+            None => return,
+        };
+        if let Some(pos) = self.marked.iter().position(|marked| *marked == name) {
+            self.marked.remove(pos);
+        } else if self.marked.len() >= MAX_COMPARE {
+            self.toast.push_back(ToastState::warn(
+                Some("Compare".to_string()),
+                format!("At most {} crates can be compared", MAX_COMPARE),
+            ));
+        } else {
+            self.marked.push(name);
+        }
+    }
+
+    fn view_compare(&mut self) {
+        if self.marked.len() < 2 {
+            self.toast.push_back(ToastState::warn(
+                Some("Compare".to_string()),
+                "Mark at least 2 crates with M to compare them".to_string(),
+            ));
+        } else {
+            self.navigate(View::Compare(self.marked.clone()));
+        }
+    }
+
+    /// The crates on the current page that pass the filters and the license policy.  When
+    /// filtering, the results that haven't been scanned yet are counted as matching, so the total
+    /// is the most there can be.
     fn get_cached_crates(&self) -> Option<(u32, Vec<&CrateSearch>)> {
-        let search = self.inpt.as_ref();
-        self.client.search_sorted_cached(
-            search.unwrap(),
-            self.page,
-            self.items_per_page,
-            &self.sort,
+        let tab = self.tab();
+        if !self.is_filtering() {
+            return self.client.search_sorted_cached(
+                &tab.query,
+                tab.page,
+                self.items_per_page,
+                &tab.sort,
+            );
+        }
+
+        let (total, scanned, matched) = self.filtered_crates()?;
+        let bound = matched.len() as u32 + (total - scanned);
+        let crates = matched
+            .into_iter()
+            .skip(((tab.page - 1) * self.items_per_page) as usize)
+            .take(self.items_per_page as usize)
+            .collect();
+        Some((bound, crates))
+    }
+
+    /// Whether the results of the current tab are filtered on the client
+    fn is_filtering(&self) -> bool {
+        self.tab().filter.is_active()
+            || self.tab().query.is_filtering()
+            || self.config.allowed_licenses.is_some()
+    }
+
+    /// The total number of results, how many have been scanned from the start and the scanned
+    /// crates that pass the filters.  The scan stops at the first crate whose license is needed
+    /// but hasn't been fetched.
+    fn filtered_crates(&self) -> Option<(u32, u32, Vec<&CrateSearch>)> {
+        let tab = self.tab();
+        let (total, crates) = self.client.search_prefix_cached(&tab.query, &tab.sort)?;
+
+        let mut scanned = 0;
+        let mut matched = Vec::new();
+        for crte in crates {
+            if tab.filter.matches(crte) && tab.query.matches(crte) {
+                match self.is_license_allowed(crte) {
+                    Some(true) => matched.push(crte),
+                    Some(false) => {}
+                    None => break,
+                }
+            }
+            scanned += 1;
+        }
+        Some((total, scanned, matched))
+    }
+
+    /// Fetches results ahead until the current page of filtered results is full, every result
+    /// has been scanned or `MAX_FETCH_AHEAD` results have been fetched.  The page is moved back
+    /// to the last one with results if it's past the end.
+    fn fill_filtered_page(&mut self) -> Result<(), reqwest::Error> {
+        let query = self.tab().query.clone();
+        let sort = self.tab().sort.clone();
+        self.client.expire_search(&query, &sort);
+        // one more than the page so it's known whether there is a next one
+        let needed = (self.tab().page * self.items_per_page + 1) as usize;
+
+        let mut fetched = 0;
+        loop {
+            if self.config.allowed_licenses.is_some() {
+                self.fetch_candidate_licenses(needed)?;
+            }
+            let (complete, matched) = match self.filtered_crates() {
+                Some((total, scanned, matched)) => (scanned >= total, matched.len()),
+                None => (false, 0),
+            };
+            let (total, cached) = self
+                .client
+                .search_prefix_cached(&query, &sort)
+                .map_or((u32::MAX, 0), |(total, crates)| {
+                    (total, crates.len() as u32)
+                });
+            if complete || matched >= needed || cached >= total || fetched >= MAX_FETCH_AHEAD {
+                let last_page = self.num_pages(matched as u32).max(1);
+                if self.tab().page > last_page {
+                    self.tab_mut().page = last_page;
+                }
+                return Ok(());
+            }
+            fetched += self.client.fetch_ahead(&query, cached, &sort)?;
+        }
+    }
+
+    /// Fetches the licenses of scanned crates that pass the tab's filters, stopping once `needed`
+    /// of them are allowed by the license policy
+    fn fetch_candidate_licenses(&mut self, needed: usize) -> Result<(), reqwest::Error> {
+        let tab = self.tab();
+        let candidates = match self.client.search_prefix_cached(&tab.query, &tab.sort) {
+            Some((_, crates)) => crates
+                .into_iter()
+                .filter(|crte| tab.filter.matches(crte) && tab.query.matches(crte))
+                .map(|crte| (crte.name.clone(), crte.newest_version.clone()))
+                .collect::<Vec<_>>(),
+            None => return Ok(()),
+        };
+
+        let mut allowed = 0;
+        for (name, version) in candidates {
+            if allowed >= needed {
+                break;
+            }
+            let license = self.client.get_version_with_cache(&name, &version)?;
+            let expr = license.license.as_deref();
+            let policy = self.config.allowed_licenses.as_deref().unwrap_or_default();
+            if expr.is_some_and(|expr| license::is_allowed(expr, policy)) {
+                allowed += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// The license of the crate's newest version, once it has been fetched
+    fn license(&self, crte: &CrateSearch) -> Option<&str> {
+        self.client
+            .get_version_cached(&crte.name, &crte.newest_version)
+            .and_then(|version| version.license.as_deref())
+    }
+
+    /// Whether the crate's license satisfies the allowed licenses in the config.  Crates without a
+    /// license expression aren't allowed.  None when the license hasn't been fetched yet.
+    fn is_license_allowed(&self, crte: &CrateSearch) -> Option<bool> {
+        let allowed = match &self.config.allowed_licenses {
+            Some(allowed) => allowed,
+            None => return Some(true),
+        };
+        let version = self
+            .client
+            .get_version_cached(&crte.name, &crte.newest_version)?;
+        Some(
+            version
+                .license
+                .as_deref()
+                .is_some_and(|expr| license::is_allowed(expr, allowed)),
         )
     }
 
+    /// Fetches the newest version of each crate on the current page for its license
+    fn fetch_licenses(&mut self) {
+        let crates = match self.get_cached_crates() {
+            Some((_, crates)) => crates
+                .into_iter()
+                .map(|crte| (crte.name.clone(), crte.newest_version.clone()))
+                .collect::<Vec<_>>(),
+            None => return,
+        };
+        for (name, version) in crates {
+            if let Err(err) = self.client.get_version_with_cache(&name, &version) {
+                self.toast.push_back(ToastState::err(
+                    Some("Cannot get license".to_string()),
+                    format!("{}", err),
+                ));
+                return;
+            }
+        }
+    }
+
     fn do_search(&mut self) {
-        let search = self.inpt.as_ref();
-        let (_, crates) = self
-            .client
-            .search_sorted_with_cache(search.unwrap(), self.page, self.items_per_page, &self.sort)
-            .unwrap_or((0, vec![]));
-        self.selection = if crates.is_empty() { None } else { Some(0) }
+        let found = if self.is_filtering() {
+            match self.fill_filtered_page() {
+                Ok(()) => true,
+                Err(err) => {
+                    self.toast.push_back(ToastState::err(
+                        Some("Request Error".to_string()),
+                        format!("{}", err),
+                    ));
+                    false
+                }
+            }
+        } else {
+            let tab = &self.tabs[self.tab];
+            self.client
+                .search_sorted_with_cache(&tab.query, tab.page, self.items_per_page, &tab.sort)
+                .is_ok()
+        };
+        if found {
+            self.fetch_licenses();
+        }
+        let empty = self
+            .get_cached_crates()
+            .is_none_or(|(_, crates)| crates.is_empty());
+        self.tab_mut().selection = if empty { None } else { Some(0) }
     }
 
-    #[cfg(feature = "browser")]
-    fn open_selection(&self) -> Result<(), Box<dyn Error>> {
-        if let Some(selection) = self.selection {
-            if let Some((_, crates)) = self.get_cached_crates() {
-                if let Some(crte) = crates.get(selection) {
-                    if let Some(docs) = &crte.documentation {
-                        open::that(docs).map_err(|err| {
-                            Box::<dyn Error>::from(format!(
-                                "Error opening documentation in browser.\n{}",
-                                err
-                            ))
-                        })?
-                    } else {
-                        Err(Box::<dyn Error>::from(
-                            "No documentation link for is given for this crate!",
-                        ))?
-                    }
+    /// Shows the menu of links for the selected crate, starting on the last link type opened
+    fn open_menu(&mut self) {
+        let crte = match self.selected_crate() {
+            Some(crte) => crte,
+            None => return,
+        };
+        let items = CrateLink::ALL
+            .iter()
+            .filter_map(|link| link.url(crte).map(|url| (*link, url)))
+            .collect::<Vec<_>>();
+        let strs = items
+            .iter()
+            .enumerate()
+            .map(|(i, (link, url))| format!("{}. {:<15}{}", i + 1, link.to_string(), url))
+            .collect();
+
+        // fall back to the closest link when the last one isn't available for this crate
+        let mut preferred = Some(self.last_link);
+        let selection = loop {
+            match preferred {
+                Some(link) => match items.iter().position(|(item, _)| *item == link) {
+                    Some(pos) => break pos,
+                    None => preferred = link.fallback(),
+                },
+                None => break 0,
+            }
+        };
+
+        self.mode = AppMode::Open(MenuField {
+            selection,
+            items,
+            strs,
+        });
+    }
+
+    /// Opens the link at the index of the open menu
+    fn open_link(&mut self, selection: usize) {
+        let (link, url) = match &self.mode {
+            AppMode::Open(menu) => menu.items[selection].clone(),
+            _ => return,
+        };
+        self.mode = AppMode::Normal;
+        self.last_link = link;
+        if let Err(msg) = self.open_url(&url) {
+            self.toast.push_back(ToastState::err(
+                Some("Cannot open in browser"),
+                format!("{}", msg).as_str(),
+            ))
+        }
+    }
+
+    /// Opens the url with the configured browser command, or `$BROWSER`.  Without either the
+    /// system's default browser is used.
+    fn open_url(&self, url: &str) -> Result<(), Box<dyn Error>> {
+        let command = self
+            .config
+            .browser
+            .clone()
+            // $BROWSER may be a ':' separated list of browsers to try
+            .or_else(|| {
+                env::var("BROWSER")
+                    .ok()?
+                    .split(':')
+                    .next()
+                    .map(String::from)
+            })
+            .filter(|command| !command.trim().is_empty());
+
+        match command {
+            Some(command) => {
+                let mut parts = command.split_whitespace();
+                let program = parts.next().unwrap();
+                let mut args = parts
+                    .map(|part| part.replace("{}", url))
+                    .collect::<Vec<_>>();
+                if !command.contains("{}") {
+                    args.push(url.to_string());
                 }
+                Command::new(program)
+                    .args(args)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .spawn()
+                    .map_err(|err| format!("Error running '{}'.\n{}", program, err))?;
+                Ok(())
             }
+            None => Self::open_default(url),
         }
+    }
 
+    #[cfg(feature = "browser")]
+    fn open_default(url: &str) -> Result<(), Box<dyn Error>> {
+        open::that(url).map_err(|err| {
+            Box::<dyn Error>::from(format!("Error opening link in browser.\n{}", err))
+        })?;
         Ok(())
     }
+
     #[cfg(not(feature = "browser"))]
-    fn open_selection(&self) -> Result<(), Box<dyn Error>> {
-        Err(Box::<dyn Error>::from("Feature Disabled"))
+    fn open_default(_url: &str) -> Result<(), Box<dyn Error>> {
+        Err(Box::<dyn Error>::from(
+            "Feature Disabled.  Set a browser command in the config file",
+        ))
     }
 
-    #[cfg(feature = "copy")]
-    fn copy_selection(&self) -> Result<(), Box<dyn Error>> {
-        if let Some(selection) = self.selection {
-            if let Some((_, crates)) = self.get_cached_crates() {
-                let toml = crates
-                    .get(selection)
-                    .map(|x| CrateSearch::get_toml_str(x.to_owned()));
-                let mut clipboard: ClipboardContext = ClipboardProvider::new()
-                    .map_err(|_err| Box::<dyn Error>::from("Error setting clipboard contents"))?;
-
-                if let Some(toml) = toml {
-                    clipboard.set_contents(toml).map_err(|_err| {
-                        Box::<dyn Error>::from("Error setting clipboard contents")
-                    })?;
+    /// Starts adding the selected crate to the nearest Cargo.toml, or one of its workspace's
+    /// members
+    fn add_start(&mut self) {
+        let name = match self.selected_crate() {
+            Some(crte) => crte.name.clone(),
+            None => return,
+        };
+
+        let candidates = env::current_dir()
+            .map_err(Box::<dyn Error>::from)
+            .and_then(|dir| Manifest::candidates(&dir));
+        let candidates = match candidates {
+            Ok(candidates) => candidates,
+            Err(err) => {
+                self.toast.push_back(ToastState::err(
+                    Some("Cannot add dependency".to_string()),
+                    format!("{}", err),
+                ));
+                return;
+            }
+        };
+
+        let cwd = env::current_dir().unwrap_or_default();
+        let strs = candidates
+            .iter()
+            .map(|path| {
+                path.strip_prefix(&cwd)
+                    .unwrap_or(path)
+                    .display()
+                    .to_string()
+            })
+            .collect();
+        // with a single manifest there is nothing to choose
+        let single = candidates.len() == 1;
+        self.mode = AppMode::Add(Box::new(AddField {
+            name,
+            manifest: None,
+            table: None,
+            version: None,
+            step: AddStep::Manifest(MenuField {
+                selection: 0,
+                items: candidates,
+                strs,
+            }),
+        }));
+        if single {
+            self.add_next();
+        }
+    }
+
+    /// Confirms the choice of the current step of adding a crate and moves on to the next one.  The
+    /// manifest is written once the preview is confirmed.
+    fn add_next(&mut self) {
+        let field = match std::mem::replace(&mut self.mode, AppMode::Normal) {
+            AppMode::Add(field) => field,
+            mode => {
+                self.mode = mode;
+                return;
+            }
+        };
+
+        match self.add_step(field) {
+            Ok(Some(field)) => self.mode = AppMode::Add(field),
+            Ok(None) => {}
+            Err(err) => self.toast.push_back(ToastState::err(
+                Some("Cannot add dependency".to_string()),
+                format!("{}", err),
+            )),
+        }
+    }
+
+    /// Moves the field to its next step, returning None once the manifest has been written
+    fn add_step(
+        &mut self,
+        mut field: Box<AddField>,
+    ) -> Result<Option<Box<AddField>>, Box<dyn Error>> {
+        let step = std::mem::replace(&mut field.step, AddStep::Preview(Vec::new(), 0));
+        field.step = match step {
+            AddStep::Manifest(menu) => {
+                let manifest = Manifest::load(&menu.items[menu.selection])?;
+                let items = manifest.tables();
+                let strs = items.iter().map(|table| table.to_string()).collect();
+                field.manifest = Some(manifest);
+                AddStep::Table(MenuField {
+                    selection: 0,
+                    items,
+                    strs,
+                })
+            }
+            AddStep::Table(menu) => {
+                field.table = Some(menu.items[menu.selection].clone());
+                let crte = self.client.get_crate_with_cache(&field.name)?;
+                let items = crte
+                    .versions
+                    .iter()
+                    .filter(|version| !version.yanked)
+                    .map(|version| version.num.clone())
+                    .collect::<Vec<_>>();
+                if items.is_empty() {
+                    return Err(format!("{} has no versions that aren't yanked", field.name).into());
+                }
+                let selection = items
+                    .iter()
+                    .position(|num| *num == crte.crte.newest_version)
+                    .unwrap_or(0);
+                AddStep::Version(MenuField {
+                    selection,
+                    strs: items.clone(),
+                    items,
+                })
+            }
+            AddStep::Version(menu) => {
+                let num = menu.items[menu.selection].clone();
+                let items = self
+                    .client
+                    .get_crate_cached(&field.name)
+                    .and_then(|crte| crte.versions.iter().find(|version| version.num == num))
+                    .map(|version| {
+                        version
+                            .features
+                            .keys()
+                            .filter(|feature| *feature != "default")
+                            .map(|feature| (feature.clone(), false))
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                self.warn_vulnerable(&field.name, &num);
+                field.version = Some(num);
+                if items.is_empty() {
+                    self.add_preview(&mut field, &[])?
+                } else {
+                    AddStep::Features(MenuField {
+                        selection: 0,
+                        strs: Self::feature_strs(&items),
+                        items,
+                    })
                 }
             }
+            AddStep::Features(menu) => {
+                let features = menu
+                    .items
+                    .into_iter()
+                    .filter(|(_, enabled)| *enabled)
+                    .map(|(feature, _)| feature)
+                    .collect::<Vec<_>>();
+                self.add_preview(&mut field, &features)?
+            }
+            AddStep::Preview(..) => {
+                let manifest = field.manifest.as_ref().ok_or("No manifest chosen")?;
+                manifest.save()?;
+                self.toast.push_back(ToastState::info(
+                    Some("Added dependency".to_string()),
+                    format!("Added {} to {}", field.name, manifest.path.display()),
+                ));
+                return Ok(None);
+            }
+        };
+
+        Ok(Some(field))
+    }
+
+    /// Adds the crate to the manifest in memory, giving the preview of the changes
+    fn add_preview(
+        &self,
+        field: &mut AddField,
+        features: &[String],
+    ) -> Result<AddStep, Box<dyn Error>> {
+        let crte = &self
+            .client
+            .get_crate_cached(&field.name)
+            .ok_or("The crate isn't loaded")?
+            .crte;
+        let version = field.version.as_deref().ok_or("No version chosen")?;
+        let table = field.table.as_ref().ok_or("No table chosen")?;
+        let manifest = field.manifest.as_mut().ok_or("No manifest chosen")?;
+
+        manifest.add_dependency(table, &crte.get_toml_str_with(version, features))?;
+        Ok(AddStep::Preview(manifest.diff(), 0))
+    }
+
+    fn feature_strs(features: &[(String, bool)]) -> Vec<String> {
+        features
+            .iter()
+            .map(|(feature, enabled)| format!("[{}] {}", if *enabled { "x" } else { " " }, feature))
+            .collect()
+    }
+
+    /// Shows the menu of formats the selected crate can be copied in.  A `[dependencies]` block is
+    /// offered when crates are marked.
+    fn copy_menu(&mut self) {
+        let mut items = match self.selected_crate() {
+            Some(crte) => CopyFormat::CRATE
+                .iter()
+                .filter_map(|format| format.text(crte).map(|text| (*format, text)))
+                .collect::<Vec<_>>(),
+            None => Vec::new(),
+        };
+        if !self.marked.is_empty() {
+            match self.marked_dependencies() {
+                Ok(block) => items.push((CopyFormat::Dependencies, block)),
+                Err(err) => self.toast.push_back(ToastState::err(
+                    Some("Cannot get marked crates".to_string()),
+                    format!("{}", err),
+                )),
+            }
+        }
+        if items.is_empty() {
+            return;
         }
+
+        let strs = items
+            .iter()
+            .enumerate()
+            .map(|(i, (format, text))| {
+                let preview = match format {
+                    CopyFormat::Dependencies => format!("{} marked crates", self.marked.len()),
+                    _ => text.clone(),
+                };
+                format!("{}. {:<22}{}", i + 1, format.to_string(), preview)
+            })
+            .collect();
+
+        self.mode = AppMode::Copy(MenuField {
+            selection: 0,
+            items,
+            strs,
+        });
+    }
+
+    /// A `[dependencies]` block for the marked crates, fetching any that aren't cached
+    fn marked_dependencies(&mut self) -> Result<String, reqwest::Error> {
+        for name in &self.marked {
+            self.client.get_crate_with_cache(name)?;
+        }
+        let crates = self
+            .marked
+            .iter()
+            .filter_map(|name| self.client.get_crate_cached(name))
+            .map(|resp| &resp.crte)
+            .collect::<Vec<_>>();
+        Ok(CopyFormat::dependencies_block(&crates))
+    }
+
+    /// Copies the text at the index of the copy menu
+    fn copy_format(&mut self, selection: usize) {
+        let (format, text) = match &self.mode {
+            AppMode::Copy(menu) => menu.items[selection].clone(),
+            _ => return,
+        };
+        self.mode = AppMode::Normal;
+        match self.set_clipboard(text) {
+            Ok(()) => self.toast.push_back(ToastState::info(
+                Some("Copied".to_string()),
+                format!("Copied the {} to the clipboard", format),
+            )),
+            Err(msg) => {
+                self.toast.push_back(ToastState::err(
+                    Some("Clipboard Error".to_string()),
+                    format!("{}", msg),
+                ));
+                return;
+            }
+        }
+
+        // the formats that pin a version
+        let copied = match format {
+            CopyFormat::Toml | CopyFormat::CargoAdd => {
+                self.selected_crate_version().into_iter().collect()
+            }
+            CopyFormat::Dependencies => self
+                .marked
+                .iter()
+                .filter_map(|name| self.client.get_crate_cached(name))
+                .map(|resp| (resp.crte.name.clone(), resp.crte.newest_version.clone()))
+                .collect(),
+            _ => Vec::new(),
+        };
+        for (name, version) in copied {
+            self.warn_vulnerable(&name, &version);
+        }
+    }
+
+    /// Copies the text using the configured clipboard backend.  The system clipboard is used when
+    /// possible, falling back to OSC 52 when there is no display or the `copy` feature is off.
+    fn set_clipboard(&self, text: String) -> Result<(), Box<dyn Error>> {
+        let osc52 = match self.config.clipboard {
+            ClipboardBackend::Osc52 => true,
+            ClipboardBackend::System => false,
+            ClipboardBackend::Auto => !cfg!(feature = "copy") || !osc52::has_display(),
+        };
+
+        if osc52 {
+            osc52::copy(&text).map_err(|err| {
+                Box::<dyn Error>::from(format!("Error writing to the terminal.\n{}", err))
+            })
+        } else {
+            Self::set_system_clipboard(text)
+        }
+    }
+
+    #[cfg(feature = "copy")]
+    fn set_system_clipboard(text: String) -> Result<(), Box<dyn Error>> {
+        let mut clipboard: ClipboardContext = ClipboardProvider::new()
+            .map_err(|_err| Box::<dyn Error>::from("Error setting clipboard contents"))?;
+
+        clipboard
+            .set_contents(text)
+            .map_err(|_err| Box::<dyn Error>::from("Error setting clipboard contents"))?;
         Ok(())
     }
 
     #[cfg(not(feature = "copy"))]
-    fn copy_selection(&self) -> Result<(), Box<dyn Error>> {
+    fn set_system_clipboard(_text: String) -> Result<(), Box<dyn Error>> {
         Err("Feature Disabled".into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recall_restores_draft() {
+        let mut history = SearchHistory::in_memory();
+        for term in ["serde", "tokio"] {
+            history.push(term, &CratesSort::Relevance).unwrap();
+        }
+        let mut input = InputField {
+            text: "draft".to_string(),
+            ..InputField::default()
+        };
+
+        input.recall_prev(&history);
+        assert_eq!(input.text, "tokio");
+        input.recall_prev(&history);
+        assert_eq!(input.text, "serde");
+        // there is nothing older
+        input.recall_prev(&history);
+        assert_eq!((input.text.as_str(), input.history_pos), ("serde", Some(1)));
+
+        input.recall_next(&history);
+        assert_eq!(input.text, "tokio");
+        input.recall_next(&history);
+        assert_eq!((input.text.as_str(), input.history_pos), ("draft", None));
+        // stepping past the typed text does nothing
+        input.recall_next(&history);
+        assert_eq!(input.text, "draft");
+    }
+
+    #[test]
+    fn recall_without_history() {
+        let history = SearchHistory::in_memory();
+        let mut input = InputField {
+            text: "draft".to_string(),
+            ..InputField::default()
+        };
+        input.recall_prev(&history);
+        assert_eq!((input.text.as_str(), input.history_pos), ("draft", None));
+    }
+
+    #[test]
+    fn edit_detaches_from_history() {
+        let mut history = SearchHistory::in_memory();
+        history.push("serde", &CratesSort::Relevance).unwrap();
+        let mut input = InputField::default();
+        input.recall_prev(&history);
+        input.edit().push_str("_json");
+        assert_eq!(
+            (input.text.as_str(), input.history_pos),
+            ("serde_json", None)
+        );
+    }
+}
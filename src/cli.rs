@@ -0,0 +1,275 @@
+use std::{
+    env,
+    error::Error,
+    ffi::OsString,
+    thread,
+    time::{Duration, Instant},
+};
+
+use reqwest::StatusCode;
+use structopt::StructOpt;
+
+use crate::{
+    config::Config,
+    crates_io::{CrateSearcher, CratesSort, SearchQuery},
+    outdated::outdated_report,
+    output::{print_record, print_records, OutputFormat, RecordWriter},
+    project::Project,
+};
+
+/// The most results crates.io gives per page
+const MAX_PER_PAGE: u32 = 100;
+/// How many dependents are printed unless --count is given
+const DEFAULT_RDEPS_COUNT: u32 = 10;
+/// crates.io asks crawlers to make at most one request a second
+const REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+/// How many times a request is retried after crates.io says too many were made
+const MAX_RETRIES: u32 = 3;
+
+/// How the results of a command are printed
+pub struct Output<'a> {
+    pub format: OutputFormat,
+    pub fields: &'a [String],
+}
+
+/// Which search results are printed.  The flags are global, so they can be given before or
+/// after the subcommand.
+#[derive(Debug, StructOpt)]
+pub struct SearchArgs {
+    /// How the results are sorted: relevance, downloads, recent-downloads, recent-updates or new.
+    /// Defaults to the sort in the config.
+    #[structopt(
+        short,
+        long,
+        global = true,
+        possible_values = &CratesSort::NAMES,
+        case_insensitive = true,
+        hide_possible_values = true
+    )]
+    pub sort: Option<CratesSort>,
+
+    /// How many results are fetched per page, at most 100.  Defaults to the page size in the
+    /// config, or to 100 with --all or --limit.
+    #[structopt(short, long, global = true)]
+    pub count: Option<u32>,
+
+    /// The page of results to print, or to start from with --all or --limit
+    #[structopt(long, global = true, default_value = "1")]
+    pub page: u32,
+
+    /// Prints every result, fetching one page after another
+    #[structopt(long, global = true)]
+    pub all: bool,
+
+    /// The most results to print, fetching one page after another until there are enough
+    #[structopt(long, global = true)]
+    pub limit: Option<usize>,
+}
+
+/// Rewrites the values of `--sort` in the command line arguments into the form of
+/// [`CratesSort::NAMES`], so that clap accepts names such as `"all time downloads"` while the
+/// completions still offer the sort names
+pub fn normalize_sort_args<I: IntoIterator<Item = OsString>>(args: I) -> Vec<OsString> {
+    let mut sort_next = false;
+    args.into_iter()
+        .map(|arg| {
+            let value = match arg.to_str() {
+                Some(arg) if sort_next => Some(("", arg)),
+                Some(arg) => arg
+                    .strip_prefix("--sort=")
+                    .map(|value| ("--sort=", value))
+                    .or_else(|| {
+                        arg.strip_prefix("-s")
+                            .filter(|value| !value.is_empty())
+                            .map(|value| ("-s", value))
+                    }),
+                None => None,
+            };
+            sort_next = matches!(arg.to_str(), Some("--sort" | "-s")) && !sort_next;
+            match value {
+                Some((flag, value)) => format!("{}{}", flag, CratesSort::normalize(value)).into(),
+                None => arg,
+            }
+        })
+        .collect()
+}
+
+/// Spaces requests out to stay within the crates.io rate limit
+#[derive(Default)]
+struct Throttle {
+    last: Option<Instant>,
+}
+
+impl Throttle {
+    /// Makes the request once enough time has passed since the last one.  When crates.io
+    /// answers that too many requests were made, it waits longer each time and retries.
+    fn request<T, F>(&mut self, request: F) -> Result<T, reqwest::Error>
+    where
+        F: Fn() -> Result<T, reqwest::Error>,
+    {
+        let mut retries = 0;
+        loop {
+            if let Some(last) = self.last {
+                thread::sleep(REQUEST_INTERVAL.saturating_sub(last.elapsed()));
+            }
+            self.last = Some(Instant::now());
+            match request() {
+                Err(err)
+                    if err.status() == Some(StatusCode::TOO_MANY_REQUESTS)
+                        && retries < MAX_RETRIES =>
+                {
+                    retries += 1;
+                    thread::sleep(REQUEST_INTERVAL * 2u32.pow(retries));
+                }
+                resp => return resp,
+            }
+        }
+    }
+}
+
+/// Prints the search results.  With `--all` or `--limit`, pages are fetched one after another
+/// and printed as they arrive.
+pub fn search(
+    crate_search: &CrateSearcher,
+    term: &str,
+    args: &SearchArgs,
+    config: &Config,
+    output: &Output,
+) -> Result<(), Box<dyn Error>> {
+    let query = term.parse::<SearchQuery>()?;
+    let sort = args.sort.as_ref().unwrap_or(&config.sort);
+    let walk = args.all || args.limit.is_some();
+    let per_page = args
+        .count
+        .unwrap_or(if walk { MAX_PER_PAGE } else { config.page_size });
+    if per_page > MAX_PER_PAGE {
+        return Err(format!(
+            "--count can be at most {}, the most results crates.io gives per page.  Use --all or \
+             --limit to print more results.",
+            MAX_PER_PAGE
+        )
+        .into());
+    }
+    if per_page == 0 || args.page == 0 {
+        return Err("--count and --page must be at least 1".into());
+    }
+
+    let mut writer = RecordWriter::new(output.format, output.fields)?;
+    let mut throttle = Throttle::default();
+    let mut remaining = args.limit.unwrap_or(usize::MAX);
+    let mut resp =
+        throttle.request(|| crate_search.search_sorted_count(&query, args.page, per_page, sort))?;
+    loop {
+        let mut crates = resp.crates;
+        // the download and update bounds are applied to the results that were fetched
+        crates.retain(|crte| query.matches(crte));
+        crates.truncate(remaining);
+        remaining -= crates.len();
+        writer.write(&crates)?;
+
+        match resp.meta.next_page {
+            Some(next_page) if walk && remaining > 0 && !writer.is_closed() => {
+                resp = throttle.request(|| crate_search.search_next_page(&next_page))?;
+            }
+            _ => break,
+        }
+    }
+    writer.finish()
+}
+
+pub fn info(
+    crate_search: &CrateSearcher,
+    name: &str,
+    output: &Output,
+) -> Result<(), Box<dyn Error>> {
+    let crte = crate_search.get_crate(name)?.crte;
+    print_record(&crte, output.format, output.fields)
+}
+
+pub fn versions(
+    crate_search: &CrateSearcher,
+    name: &str,
+    output: &Output,
+) -> Result<(), Box<dyn Error>> {
+    let versions = crate_search.get_crate(name)?.versions;
+    print_records(&versions, output.format, output.fields)
+}
+
+/// Prints the dependencies of `crate@version`, or of the newest version when none is given
+pub fn deps(
+    crate_search: &CrateSearcher,
+    spec: &str,
+    output: &Output,
+) -> Result<(), Box<dyn Error>> {
+    let (name, version) = match spec.split_once('@') {
+        Some((name, version)) => (name.to_string(), version.to_string()),
+        None => {
+            let crte = crate_search.get_crate(spec)?.crte;
+            (crte.name, crte.newest_version)
+        }
+    };
+    let deps = crate_search.get_dependencies(&name, &version)?.dependencies;
+    print_records(&deps, output.format, output.fields)
+}
+
+/// Prints the crates that depend on a crate, `count` of them or 10 when `None`.  Only the first
+/// page of dependents is fetched, so there can be at most 100.
+pub fn rdeps(
+    crate_search: &CrateSearcher,
+    name: &str,
+    count: Option<u32>,
+    output: &Output,
+) -> Result<(), Box<dyn Error>> {
+    let count = count.unwrap_or(DEFAULT_RDEPS_COUNT);
+    if !(1..=MAX_PER_PAGE).contains(&count) {
+        return Err(format!(
+            "--count must be between 1 and {}, the most dependents crates.io gives at once",
+            MAX_PER_PAGE
+        )
+        .into());
+    }
+    let resp = crate_search.get_reverse_dependencies(name, 1, count)?;
+    print_records(&resp.dependents(), output.format, output.fields)
+}
+
+pub fn owners(
+    crate_search: &CrateSearcher,
+    name: &str,
+    output: &Output,
+) -> Result<(), Box<dyn Error>> {
+    let owners = crate_search.get_owners(name)?.users;
+    print_records(&owners, output.format, output.fields)
+}
+
+/// Prints how up to date the dependencies of the Cargo project in the working directory are
+pub fn outdated(crate_search: &mut CrateSearcher, output: &Output) -> Result<(), Box<dyn Error>> {
+    let project = Project::detect(&env::current_dir()?)
+        .ok_or("No Cargo project found in the working directory")?;
+    let report = outdated_report(&project, crate_search);
+    print_records(&report, output.format, output.fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(args: &[&str]) -> Vec<OsString> {
+        normalize_sort_args(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn sort_args_are_normalized() {
+        assert_eq!(
+            normalize(&["cratuity", "--sort", "All Time_Downloads", "all time"]),
+            ["cratuity", "--sort", "all-time-downloads", "all time"]
+        );
+        assert_eq!(
+            normalize(&["cratuity", "--sort=Recent Update", "-sNEW", "-s", "rel"]),
+            ["cratuity", "--sort=recent-update", "-snew", "-s", "rel"]
+        );
+        assert_eq!(
+            normalize(&["cratuity", "-i", "a b"]),
+            ["cratuity", "-i", "a b"]
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::format::{Item, StrftimeItems};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crates_io::{CratesSort, CRATES_IO_API},
+    format::{CountStyle, DateStyle, Formatting, DEFAULT_DATE_FORMAT},
+    keymap::Keymap,
+    theme::Theme,
+    MAX_ITEMS_PER_PAGE,
+};
+
+/// The name of the registry searched unless the config says otherwise
+const DEFAULT_REGISTRY: &str = "crates-io";
+
+/// The config written by `cratuity config init`, with every setting at its default
+const TEMPLATE: &str = r##"# The sort searches start with: relevance, downloads, recent-downloads, recent-updates or new
+# sort = "relevance"
+# How many crates are shown on each page of the TUI, up to 10
+# page-size = 5
+# How dates are shown: "format" with date-format, "iso" such as 2024-01-31, or "relative"
+# such as 3 days ago
+# date-style = "format"
+# See https://docs.rs/chrono/latest/chrono/format/strftime
+# date-format = "%x %H:%M"
+# How counts such as downloads are shown: "plain", "separated" such as 184,729,384, or
+# "humanized" such as 184.7M.  The separators follow the locale.
+# count-style = "separated"
+# How many seconds search results and crate details are kept before being fetched again.
+# Kept for as long as cratuity runs when unset.
+# cache-ttl = 300
+# The registry to search, "crates-io" or one from [registries]
+# registry = "crates-io"
+# The command used to open links.  Any {} is replaced with the link, otherwise the link is
+# added to the end.  $BROWSER or the system's default browser is used when unset.
+# browser = "firefox --new-tab {}"
+# How text is copied: "auto", "system" or "osc52"
+# clipboard = "auto"
+# A local clone of https://github.com/rustsec/advisory-db
+# advisory-db = "/home/me/src/advisory-db"
+# Hide search results whose license can't be satisfied with these SPDX IDs
+# allowed-licenses = ["MIT", "Apache-2.0"]
+
+# Colors are names such as "light-blue", ANSI color indexes such as "208" or hex colors
+[theme]
+# highlight = "red"
+# header = "blue"
+# muted = "dark-gray"
+
+# Rebinds the keys used while browsing, e.g. next-page = "l"
+[keymap]
+
+# Registries with an API like crates.io's, e.g. a mirror
+[registries]
+# mirror = { api = "https://crates.example.com/api/v1" }
+"##;
+
+/// User preferences read from `config.toml` in the user's config directory
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Config {
+    /// The sort searches start with
+    #[serde(with = "sort_name")]
+    pub sort: CratesSort,
+    /// How many crates are shown on each page of the TUI
+    pub page_size: u32,
+    /// How dates are shown
+    pub date_style: DateStyle,
+    /// The chrono format string dates are shown with in the `format` date style
+    pub date_format: String,
+    /// How counts such as downloads are shown
+    pub count_style: CountStyle,
+    /// How many seconds search results and crate details are cached.  Forever when unset.
+    pub cache_ttl: Option<u64>,
+    /// The name of the registry to search, crates.io or one from `registries`
+    pub registry: String,
+    /// The command used to open links, e.g. `firefox --new-window`.  Any `{}` is replaced with
+    /// the link, otherwise the link is added as the last argument.
+    pub browser: Option<String>,
+    /// How text is copied to the clipboard
+    pub clipboard: ClipboardBackend,
+    /// A local clone of the RustSec advisory database, used to flag vulnerable crates
+    pub advisory_db: Option<PathBuf>,
+    /// The SPDX license IDs crates may use.  Search results whose license can't be satisfied with
+    /// these are hidden.  No filter when unset.
+    pub allowed_licenses: Option<Vec<String>>,
+    /// The colors of the TUI
+    pub theme: Theme,
+    /// The keys rebound while browsing
+    pub keymap: Keymap,
+    /// Other registries with the crates.io API, by name
+    pub registries: BTreeMap<String, Registry>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            sort: CratesSort::default(),
+            page_size: 5,
+            date_style: DateStyle::default(),
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            count_style: CountStyle::default(),
+            cache_ttl: None,
+            registry: DEFAULT_REGISTRY.to_string(),
+            browser: None,
+            clipboard: ClipboardBackend::default(),
+            advisory_db: None,
+            allowed_licenses: None,
+            theme: Theme::default(),
+            keymap: Keymap::default(),
+            registries: BTreeMap::new(),
+        }
+    }
+}
+
+/// A registry with the crates.io API
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Registry {
+    /// The base of the API, e.g. `https://crates.io/api/v1`
+    pub api: String,
+}
+
+/// The ways of setting the clipboard
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClipboardBackend {
+    /// The system clipboard when a display is available, otherwise OSC 52
+    #[default]
+    Auto,
+    /// The system clipboard, which needs the `copy` feature
+    System,
+    /// An OSC 52 escape sequence, which the terminal uses to set the clipboard
+    Osc52,
+}
+
+impl std::str::FromStr for ClipboardBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "system" => Ok(Self::System),
+            "osc52" => Ok(Self::Osc52),
+            _ => Err(format!(
+                "Unknown clipboard {}, expected auto, system or osc52",
+                s
+            )),
+        }
+    }
+}
+
+impl Config {
+    /// The location of the config file
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("cratuity").join("config.toml"))
+    }
+
+    /// Loads the config file.  A missing file gives the default config, but an invalid one is an
+    /// error so that mistakes aren't silently ignored.
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(Self::default()),
+        };
+
+        let config: Self = match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents)
+                .map_err(|err| format!("Invalid config file {}: {}", path.display(), err))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(err) => {
+                return Err(format!("Cannot read config file {}: {}", path.display(), err).into())
+            }
+        };
+        config
+            .validate()
+            .map_err(|err| format!("Invalid config file {}: {}", path.display(), err))?;
+        Ok(config)
+    }
+
+    /// Checks the settings that can't be checked while parsing.  Run again after the command line
+    /// overrides the config.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_ITEMS_PER_PAGE).contains(&self.page_size) {
+            return Err(format!(
+                "page-size must be between 1 and {}",
+                MAX_ITEMS_PER_PAGE
+            ));
+        }
+        if StrftimeItems::new(&self.date_format).any(|item| item == Item::Error) {
+            return Err(format!("date-format {} is invalid", self.date_format));
+        }
+        self.keymap.validate()?;
+        let api = self.api()?;
+        Url::parse(api).map_err(|err| format!("Invalid API URL {}: {}", api, err))?;
+        Ok(())
+    }
+
+    /// The base of the API of the registry that is searched
+    pub fn api(&self) -> Result<&str, String> {
+        match self.registries.get(&self.registry) {
+            Some(registry) => Ok(registry.api.trim_end_matches('/')),
+            None if self.registry == DEFAULT_REGISTRY => Ok(CRATES_IO_API),
+            None => Err(format!(
+                "Unknown registry {}, add it to [registries] in the config",
+                self.registry
+            )),
+        }
+    }
+
+    /// How long fetched data is kept, forever when `None`
+    pub fn cache_ttl(&self) -> Option<Duration> {
+        self.cache_ttl.map(Duration::from_secs)
+    }
+
+    /// Sets the theme and how dates and counts are shown
+    pub fn init_display(&self) {
+        Theme::init(self.theme.clone());
+        Formatting::init(Formatting {
+            date_style: self.date_style,
+            date_format: self.date_format.clone(),
+            count_style: self.count_style,
+        });
+    }
+
+    /// The config as TOML
+    pub fn to_toml(&self) -> Result<String, Box<dyn Error>> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    /// Writes a config file with every setting commented out at its default
+    pub fn init(path: &Path) -> Result<(), Box<dyn Error>> {
+        if path.exists() {
+            return Err(format!("{} already exists", path.display()).into());
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, TEMPLATE)?;
+        Ok(())
+    }
+}
+
+/// Reads and writes the sort by the name crates.io uses, rather than the variant name that the
+/// history file uses
+mod sort_name {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    use crate::crates_io::CratesSort;
+
+    pub fn serialize<S: Serializer>(sort: &CratesSort, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&sort.to_sort_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<CratesSort, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
@@ -1,16 +1,24 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    time::{Duration, Instant},
+};
 
 use crate::ceil_div;
 use std::str::FromStr;
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, NaiveDate};
 use reqwest::{blocking::Client, Url};
 use serde::{Deserialize, Serialize};
 
-const CRATES_URL: &str = "https://crates.io/api/v1/crates";
+/// The base of the crates.io API, used unless another registry is configured
+pub const CRATES_IO_API: &str = "https://crates.io/api/v1";
+/// How many results are fetched at once when fetching ahead.  The most crates.io allows.
+const FETCH_AHEAD: u32 = 100;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CratesSort {
+    #[default]
     Relevance,
     AllTimeDownload,
     RecentDownload,
@@ -19,6 +27,32 @@ pub enum CratesSort {
 }
 
 impl CratesSort {
+    /// Every name a sort can be given by: the name crates.io uses, the display name and the
+    /// short aliases.  Spaces and underscores may be used in place of the hyphens.
+    pub const NAMES: [&'static str; 15] = [
+        "relevance",
+        "rel",
+        "downloads",
+        "all-time-downloads",
+        "all-time",
+        "dl",
+        "recent-downloads",
+        "recent",
+        "recent-updates",
+        "recently-updated",
+        "recent-update",
+        "updated",
+        "new",
+        "newly-added",
+        "newest",
+    ];
+
+    /// The name in the form of [`Self::NAMES`]: lowercase, with hyphens for spaces and
+    /// underscores
+    pub fn normalize(name: &str) -> String {
+        name.trim().to_lowercase().replace(&[' ', '_'][..], "-")
+    }
+
     pub fn to_sort_string(&self) -> String {
         match self {
             CratesSort::Relevance => "relevance".to_string(),
@@ -30,27 +64,23 @@ impl CratesSort {
     }
 }
 
-impl Default for CratesSort {
-    fn default() -> Self {
-        Self::Relevance
-    }
-}
-
 impl FromStr for CratesSort {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "relevance" => Ok(Self::Relevance),
-            "all time downloads" | "all-time-downloads" | "all_time_downloads" => {
-                Ok(Self::Relevance)
-            }
-            "recent downloads" | "recent-downloads" | "recent_downloads" => {
-                Ok(Self::RecentDownload)
+        match Self::normalize(s).as_str() {
+            "relevance" | "rel" => Ok(Self::Relevance),
+            "downloads" | "all-time-downloads" | "all-time" | "dl" => Ok(Self::AllTimeDownload),
+            "recent-downloads" | "recent" => Ok(Self::RecentDownload),
+            "recent-updates" | "recently-updated" | "recent-update" | "updated" => {
+                Ok(Self::RecentUpdate)
             }
-            "recent update" | "recent-update" | "recent_update" => Ok(Self::RecentUpdate),
-            "newly added" | "newly-added" | "newly_added" => Ok(Self::NewlyAdded),
-            _ => Err(format!("Unknown sort method {}", s)),
+            "new" | "newly-added" | "newest" => Ok(Self::NewlyAdded),
+            _ => Err(format!(
+                "Unknown sort method {}, expected one of {}",
+                s,
+                Self::NAMES.join(", ")
+            )),
         }
     }
 }
@@ -67,6 +97,257 @@ impl std::fmt::Display for CratesSort {
     }
 }
 
+/// The qualifiers understood in a search, e.g. `category:parsing`
+const QUALIFIERS: [&str; 6] = [
+    "category",
+    "keyword",
+    "all_keywords",
+    "user",
+    "updated",
+    "downloads",
+];
+
+/// What to search crates.io for: some text, optionally narrowed to a category, keywords or a
+/// user's crates.  The download and update bounds aren't supported by the API, so they are
+/// applied to the results on the client.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SearchQuery {
+    pub term: String,
+    /// The slug of a category, such as `parsing` or `web-programming::http-client`
+    pub category: Option<String>,
+    pub keyword: Option<String>,
+    /// Keywords that the crates must all have
+    pub all_keywords: Vec<String>,
+    /// The login of a user whose crates are searched
+    pub user: Option<String>,
+    pub updated: Option<Bound<NaiveDate>>,
+    pub downloads: Option<Bound<u64>>,
+}
+
+/// A comparison in a query, such as the `>1m` of `downloads:>1m`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Bound<T> {
+    Above(T),
+    AtLeast(T),
+    Below(T),
+    AtMost(T),
+}
+
+impl<T: PartialOrd> Bound<T> {
+    pub fn contains(&self, value: &T) -> bool {
+        match self {
+            Bound::Above(bound) => value > bound,
+            Bound::AtLeast(bound) => value >= bound,
+            Bound::Below(bound) => value < bound,
+            Bound::AtMost(bound) => value <= bound,
+        }
+    }
+
+    /// Reads a bound such as `>=2024-01-01`, using `parse` for the value after the operator
+    fn parse<F>(s: &str, parse: F) -> Option<Self>
+    where
+        F: Fn(&str) -> Option<T>,
+    {
+        let (make, value): (fn(T) -> Self, &str) = if let Some(value) = s.strip_prefix(">=") {
+            (Bound::AtLeast, value)
+        } else if let Some(value) = s.strip_prefix("<=") {
+            (Bound::AtMost, value)
+        } else if let Some(value) = s.strip_prefix('>') {
+            (Bound::Above, value)
+        } else if let Some(value) = s.strip_prefix('<') {
+            (Bound::Below, value)
+        } else {
+            return None;
+        };
+        parse(value).map(make)
+    }
+
+    /// Writes the bound with `format` used for the value
+    fn format<F>(&self, format: F) -> String
+    where
+        F: Fn(&T) -> String,
+    {
+        match self {
+            Bound::Above(value) => format!(">{}", format(value)),
+            Bound::AtLeast(value) => format!(">={}", format(value)),
+            Bound::Below(value) => format!("<{}", format(value)),
+            Bound::AtMost(value) => format!("<={}", format(value)),
+        }
+    }
+}
+
+/// Reads a download count such as `1000`, `10k`, `1.5m` or `2b`
+fn parse_count(s: &str) -> Option<u64> {
+    let s = s.to_lowercase();
+    let (number, scale) = match s.chars().last()? {
+        'k' => (&s[..s.len() - 1], 1e3),
+        'm' => (&s[..s.len() - 1], 1e6),
+        'b' => (&s[..s.len() - 1], 1e9),
+        _ => (s.as_str(), 1.0),
+    };
+    let number = number.parse::<f64>().ok()?;
+    if number.is_finite() && number >= 0.0 {
+        Some((number * scale).round() as u64)
+    } else {
+        None
+    }
+}
+
+/// Writes a download count the way [`parse_count`] reads it, shortened when it's round
+fn format_count(count: u64) -> String {
+    match count {
+        0 => "0".to_string(),
+        n if n % 1_000_000_000 == 0 => format!("{}b", n / 1_000_000_000),
+        n if n % 1_000_000 == 0 => format!("{}m", n / 1_000_000),
+        n if n % 1_000 == 0 => format!("{}k", n / 1_000),
+        n => n.to_string(),
+    }
+}
+
+impl SearchQuery {
+    /// Whether any part of the query is applied on the client
+    pub fn is_filtering(&self) -> bool {
+        self.updated.is_some() || self.downloads.is_some()
+    }
+
+    /// Whether the crate passes the parts of the query that are applied on the client
+    pub fn matches(&self, crte: &CrateSearch) -> bool {
+        let updated = crte.updated_at.naive_local().date();
+        self.updated.is_none_or(|bound| bound.contains(&updated))
+            && self
+                .downloads
+                .is_none_or(|bound| bound.contains(&crte.downloads))
+    }
+}
+
+/// Reads a search such as `serde category:encoding downloads:>1m`.  Words that start with one of
+/// the [`QUALIFIERS`] and a colon narrow the search, and the rest are searched for as text.  Any
+/// other word that looks like a qualifier is an error, so typos aren't silently searched for.
+impl FromStr for SearchQuery {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut query = SearchQuery::default();
+        let mut words = Vec::new();
+        for word in s.split_whitespace() {
+            // URLs such as `https://docs.rs` and paths such as `std::fmt` are searched for as text
+            let (name, value) = match word.split_once(':') {
+                Some((name, value))
+                    if !name.is_empty()
+                        && !value.starts_with(':')
+                        && !value.starts_with("//")
+                        && name.chars().all(|c| c.is_ascii_alphabetic() || c == '_') =>
+                {
+                    (name.to_lowercase(), value)
+                }
+                _ => {
+                    words.push(word);
+                    continue;
+                }
+            };
+            if !QUALIFIERS.contains(&name.as_str()) {
+                return Err(format!(
+                    "Unknown qualifier `{}:`.  Use one of {}.",
+                    name,
+                    QUALIFIERS
+                        .iter()
+                        .map(|qualifier| format!("{}:", qualifier))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+            if value.is_empty() {
+                return Err(format!(
+                    "`{}:` needs a value, e.g. {}",
+                    name,
+                    example(&name)
+                ));
+            }
+            let invalid = || {
+                format!(
+                    "Invalid `{}:` value `{}`, e.g. {}",
+                    name,
+                    value,
+                    example(&name)
+                )
+            };
+            let repeated = || format!("`{}:` can only be given once", name);
+            match name.as_str() {
+                "category" if query.category.is_some() => return Err(repeated()),
+                "category" => query.category = Some(value.to_string()),
+                "keyword" if query.keyword.is_some() => return Err(repeated()),
+                "keyword" => query.keyword = Some(value.to_string()),
+                "all_keywords" => query.all_keywords.extend(
+                    value
+                        .split(',')
+                        .filter(|keyword| !keyword.is_empty())
+                        .map(str::to_string),
+                ),
+                "user" if query.user.is_some() => return Err(repeated()),
+                "user" => query.user = Some(value.to_string()),
+                "updated" if query.updated.is_some() => return Err(repeated()),
+                "updated" => {
+                    let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok();
+                    query.updated = Some(Bound::parse(value, date).ok_or_else(invalid)?);
+                }
+                "downloads" if query.downloads.is_some() => return Err(repeated()),
+                "downloads" => {
+                    query.downloads = Some(Bound::parse(value, parse_count).ok_or_else(invalid)?);
+                }
+                _ => unreachable!(),
+            }
+        }
+        query.term = words.join(" ");
+        Ok(query)
+    }
+}
+
+/// An example of the qualifier, shown when it's misused
+fn example(qualifier: &str) -> &'static str {
+    match qualifier {
+        "category" => "category:parsing",
+        "keyword" => "keyword:async",
+        "all_keywords" => "all_keywords:async,tokio",
+        "user" => "user:dtolnay",
+        "updated" => "updated:>2024-01-01",
+        _ => "downloads:>1m",
+    }
+}
+
+impl fmt::Display for SearchQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(category) = &self.category {
+            parts.push(format!("category:{}", category));
+        }
+        if let Some(keyword) = &self.keyword {
+            parts.push(format!("keyword:{}", keyword));
+        }
+        if !self.all_keywords.is_empty() {
+            parts.push(format!("all_keywords:{}", self.all_keywords.join(",")));
+        }
+        if let Some(user) = &self.user {
+            parts.push(format!("user:{}", user));
+        }
+        if let Some(updated) = &self.updated {
+            parts.push(format!(
+                "updated:{}",
+                updated.format(|date| date.to_string())
+            ));
+        }
+        if let Some(downloads) = &self.downloads {
+            parts.push(format!(
+                "downloads:{}",
+                downloads.format(|n| format_count(*n))
+            ));
+        }
+        if !self.term.is_empty() {
+            parts.push(self.term.clone());
+        }
+        f.write_str(&parts.join(" "))
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CrateSearchResponse {
     pub meta: CrateSearchResponseMeta,
@@ -93,21 +374,346 @@ pub struct CrateSearch {
     pub description: Option<String>,
     pub documentation: Option<String>,
     pub repository: Option<String>,
+    #[serde(default)]
+    pub homepage: Option<String>,
     pub links: CrateSearchLinks,
+    #[serde(default)]
     pub exact_match: bool,
+    /// Whether every version of the crate has been yanked
+    #[serde(default)]
+    pub yanked: bool,
 }
 
 impl CrateSearch {
-    #[cfg(feature = "copy")]
     pub fn get_toml_str(&self) -> String {
-        format!("{} = \"{}\"", self.id, self.newest_version)
+        self.get_toml_str_with(&self.newest_version, &[])
+    }
+
+    /// The Cargo.toml line for a version of the crate with the features enabled
+    pub fn get_toml_str_with(&self, version: &str, features: &[String]) -> String {
+        if features.is_empty() {
+            format!("{} = \"{}\"", self.id, version)
+        } else {
+            let features = features
+                .iter()
+                .map(|feature| format!("\"{}\"", feature))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "{} = {{ version = \"{}\", features = [{}] }}",
+                self.id, version, features
+            )
+        }
     }
 }
 
+/// The ways a crate can be copied to the clipboard
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CopyFormat {
+    Toml,
+    CargoAdd,
+    CratesIo,
+    DocsRs,
+    Repository,
+    Markdown,
+    /// A `[dependencies]` block of all the marked crates
+    Dependencies,
+}
+
+impl CopyFormat {
+    /// The formats for a single crate
+    pub const CRATE: [CopyFormat; 6] = [
+        CopyFormat::Toml,
+        CopyFormat::CargoAdd,
+        CopyFormat::CratesIo,
+        CopyFormat::DocsRs,
+        CopyFormat::Repository,
+        CopyFormat::Markdown,
+    ];
+
+    /// The text to copy for the crate.  None if the crate doesn't have what the format needs.
+    pub fn text(&self, crte: &CrateSearch) -> Option<String> {
+        match self {
+            CopyFormat::Toml => Some(crte.get_toml_str()),
+            CopyFormat::CargoAdd => Some(format!("cargo add {}@{}", crte.id, crte.newest_version)),
+            CopyFormat::CratesIo => CrateLink::CratesIo.url(crte),
+            CopyFormat::DocsRs => CrateLink::DocsRs.url(crte),
+            CopyFormat::Repository => CrateLink::Repository.url(crte),
+            CopyFormat::Markdown => CrateLink::CratesIo
+                .url(crte)
+                .map(|url| format!("[{}]({})", crte.name, url)),
+            CopyFormat::Dependencies => Some(Self::dependencies_block(&[crte])),
+        }
+    }
+
+    /// A `[dependencies]` table with the newest version of each crate
+    pub fn dependencies_block(crates: &[&CrateSearch]) -> String {
+        let mut block = String::from("[dependencies]\n");
+        for crte in crates {
+            block.push_str(&crte.get_toml_str());
+            block.push('\n');
+        }
+        block
+    }
+}
+
+impl std::fmt::Display for CopyFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CopyFormat::Toml => f.write_str("Cargo.toml line"),
+            CopyFormat::CargoAdd => f.write_str("cargo add command"),
+            CopyFormat::CratesIo => f.write_str("crates.io URL"),
+            CopyFormat::DocsRs => f.write_str("docs.rs URL"),
+            CopyFormat::Repository => f.write_str("Repository URL"),
+            CopyFormat::Markdown => f.write_str("Markdown link"),
+            CopyFormat::Dependencies => f.write_str("[dependencies] block"),
+        }
+    }
+}
+
+/// The places a crate can be opened in the browser
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrateLink {
+    Documentation,
+    DocsRs,
+    Repository,
+    Homepage,
+    CratesIo,
+    LibRs,
+}
+
+impl CrateLink {
+    pub const ALL: [CrateLink; 6] = [
+        CrateLink::Documentation,
+        CrateLink::DocsRs,
+        CrateLink::Repository,
+        CrateLink::Homepage,
+        CrateLink::CratesIo,
+        CrateLink::LibRs,
+    ];
+
+    /// The link to use instead when this one isn't available for a crate
+    pub fn fallback(&self) -> Option<CrateLink> {
+        match self {
+            CrateLink::Documentation => Some(CrateLink::DocsRs),
+            CrateLink::Repository => Some(CrateLink::Homepage),
+            CrateLink::Homepage => Some(CrateLink::CratesIo),
+            CrateLink::DocsRs | CrateLink::CratesIo | CrateLink::LibRs => None,
+        }
+    }
+
+    /// The link for the crate.  docs.rs, crates.io and lib.rs links can always be derived from
+    /// the name, the others are only available if the crate's metadata gives them.
+    pub fn url(&self, crte: &CrateSearch) -> Option<String> {
+        match self {
+            CrateLink::Documentation => crte.documentation.clone(),
+            CrateLink::DocsRs => Some(format!("https://docs.rs/{}", crte.name)),
+            CrateLink::Repository => crte.repository.clone(),
+            CrateLink::Homepage => crte.homepage.clone(),
+            CrateLink::CratesIo => Some(format!("https://crates.io/crates/{}", crte.name)),
+            CrateLink::LibRs => Some(format!("https://lib.rs/crates/{}", crte.name)),
+        }
+    }
+}
+
+impl std::fmt::Display for CrateLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrateLink::Documentation => f.write_str("Documentation"),
+            CrateLink::DocsRs => f.write_str("docs.rs"),
+            CrateLink::Repository => f.write_str("Repository"),
+            CrateLink::Homepage => f.write_str("Homepage"),
+            CrateLink::CratesIo => f.write_str("crates.io"),
+            CrateLink::LibRs => f.write_str("lib.rs"),
+        }
+    }
+}
+
+/// The response for a single crate, including all of its versions
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CrateResponse {
+    #[serde(rename = "crate")]
+    pub crte: CrateSearch,
+    pub versions: Vec<CrateVersion>,
+}
+
+impl CrateResponse {
+    /// The version matching the crate's `newest_version`
+    pub fn newest_version(&self) -> Option<&CrateVersion> {
+        self.versions
+            .iter()
+            .find(|version| version.num == self.crte.newest_version)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CrateVersion {
+    pub id: u64,
+    pub num: String,
+    pub created_at: DateTime<Local>,
+    pub updated_at: DateTime<Local>,
+    pub downloads: u64,
+    pub yanked: bool,
+    pub license: Option<String>,
+    #[serde(default)]
+    pub rust_version: Option<String>,
+    #[serde(default)]
+    pub crate_size: Option<u64>,
+    /// The crate's features and what each enables
+    #[serde(default)]
+    pub features: BTreeMap<String, Vec<String>>,
+}
+
+/// The response for a single version of a crate
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VersionResponse {
+    pub version: CrateVersion,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DependenciesResponse {
+    pub dependencies: Vec<Dependency>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Dependency {
+    pub crate_id: String,
+    pub req: String,
+    pub kind: String,
+    pub optional: bool,
+    pub default_features: bool,
+    pub features: Vec<String>,
+    pub target: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReverseDependenciesResponse {
+    pub dependencies: Vec<ReverseDependency>,
+    /// The versions of the dependent crates that have the dependencies
+    pub versions: Vec<DependentVersion>,
+    pub meta: ListMeta,
+}
+
+impl ReverseDependenciesResponse {
+    /// The crates that depend on the crate, each with the version that does
+    pub fn dependents(&self) -> Vec<Dependent> {
+        self.dependencies
+            .iter()
+            .filter_map(|dep| {
+                let version = self.versions.iter().find(|ver| ver.id == dep.version_id)?;
+                Some(Dependent {
+                    name: version.crte.clone(),
+                    version: version.num.clone(),
+                    req: dep.req.clone(),
+                    kind: dep.kind.clone(),
+                    optional: dep.optional,
+                    downloads: dep.downloads,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReverseDependency {
+    pub version_id: u64,
+    pub req: String,
+    pub kind: String,
+    pub optional: bool,
+    pub downloads: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DependentVersion {
+    pub id: u64,
+    #[serde(rename = "crate")]
+    pub crte: String,
+    pub num: String,
+}
+
+/// A crate that depends on another one
+#[derive(Serialize, Debug)]
+pub struct Dependent {
+    pub name: String,
+    /// The newest version of the crate
+    pub version: String,
+    /// The requirement on the other crate
+    pub req: String,
+    pub kind: String,
+    pub optional: bool,
+    pub downloads: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UserResponse {
+    pub user: User,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct User {
+    pub id: u64,
+    pub login: String,
+    pub name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OwnersResponse {
+    pub users: Vec<Owner>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Owner {
+    pub id: u64,
+    pub login: String,
+    pub name: Option<String>,
+    pub kind: String,
+    pub url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CategoriesResponse {
+    pub categories: Vec<Category>,
+    pub meta: ListMeta,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CategoryResponse {
+    pub category: Category,
+}
+
+/// A crates.io category.  Only the response for a single category has its subcategories.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Category {
+    pub slug: String,
+    /// The name of the category.  Subcategories are named like `Web programming::HTTP client`.
+    pub category: String,
+    pub description: String,
+    pub crates_cnt: u64,
+    #[serde(default)]
+    pub subcategories: Vec<Category>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KeywordsResponse {
+    pub keywords: Vec<Keyword>,
+    pub meta: ListMeta,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Keyword {
+    pub keyword: String,
+    pub crates_cnt: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListMeta {
+    pub total: u32,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CrateSearchLinks {
     pub version_downloads: String,
-    pub versions: String,
+    pub versions: Option<String>,
     pub owners: String,
     pub owner_team: String,
     pub owner_user: String,
@@ -117,18 +723,72 @@ pub struct CrateSearchLinks {
 /// A struct that will be used to search crates.io
 pub struct CrateSearcher {
     client: Client,
-    search_cache: HashMap<(String, String), (u32, HashMap<u32, CrateSearch>)>,
+    /// the base of the registry's API, e.g. `https://crates.io/api/v1`
+    api: String,
+    /// how long search results and crates are cached, forever when `None`
+    cache_ttl: Option<Duration>,
+    /// when each search was first fetched, to expire it after the cache TTL
+    search_fetched_at: HashMap<(SearchQuery, String), Instant>,
+    /// when each crate was fetched, to expire it after the cache TTL
+    crate_fetched_at: HashMap<String, Instant>,
+    search_cache: HashMap<(SearchQuery, String), (u32, HashMap<u32, CrateSearch>)>,
+    crate_cache: HashMap<String, CrateResponse>,
+    version_cache: HashMap<(String, String), CrateVersion>,
+    dependency_cache: HashMap<(String, String), Vec<Dependency>>,
+    owner_cache: HashMap<String, Vec<Owner>>,
+    readme_cache: HashMap<(String, String), String>,
+    categories_cache: Option<Vec<Category>>,
+    subcategory_cache: HashMap<String, Vec<Category>>,
+    keyword_cache: Option<Vec<Keyword>>,
+    /// user IDs by login, used to search a user's crates
+    user_cache: HashMap<String, u64>,
 }
 
 impl CrateSearcher {
-    pub fn new() -> Result<Self, reqwest::Error> {
+    pub fn new(api: &str, cache_ttl: Option<Duration>) -> Result<Self, reqwest::Error> {
         Ok(Self {
             client: Client::builder()
                 .user_agent("craters-tui-searcher")
                 .build()?,
+            api: api.to_string(),
+            cache_ttl,
+            search_fetched_at: HashMap::new(),
+            crate_fetched_at: HashMap::new(),
             search_cache: HashMap::new(),
+            crate_cache: HashMap::new(),
+            version_cache: HashMap::new(),
+            dependency_cache: HashMap::new(),
+            owner_cache: HashMap::new(),
+            readme_cache: HashMap::new(),
+            categories_cache: None,
+            subcategory_cache: HashMap::new(),
+            keyword_cache: None,
+            user_cache: HashMap::new(),
         })
     }
+
+    /// The URL of an endpoint of the API, e.g. `crates`
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.api, path)
+    }
+
+    /// Whether something fetched at the time is older than the cache TTL
+    fn is_stale(&self, fetched_at: Option<&Instant>) -> bool {
+        match (self.cache_ttl, fetched_at) {
+            (Some(ttl), Some(fetched_at)) => fetched_at.elapsed() > ttl,
+            _ => false,
+        }
+    }
+
+    /// Drops the cached results of the search once they're older than the cache TTL, so that
+    /// they're fetched again
+    pub fn expire_search(&mut self, query: &SearchQuery, sort: &CratesSort) {
+        let key = (query.clone(), sort.to_sort_string());
+        if self.is_stale(self.search_fetched_at.get(&key)) {
+            self.search_cache.remove(&key);
+            self.search_fetched_at.remove(&key);
+        }
+    }
 }
 fn get_all_items(
     page_cache: &HashMap<u32, CrateSearch>,
@@ -147,16 +807,22 @@ fn get_all_items(
 
 impl CrateSearcher {
     /// Adds the search query results to the internal cache.
-    pub fn search_and_add_to_cache<T: AsRef<str>>(
+    pub fn search_and_add_to_cache(
         &mut self,
-        term: T,
+        query: &SearchQuery,
         page: u32,
         items_per_page: u32,
         sort: &CratesSort,
     ) -> Result<(), reqwest::Error> {
-        let key = (term.as_ref().to_string(), sort.to_sort_string());
-        let resp = self.search_sorted(term, page, items_per_page, sort)?;
+        let key = (query.clone(), sort.to_sort_string());
+        if let Some(login) = &query.user {
+            self.get_user_id_with_cache(login)?;
+        }
+        let resp = self.search_sorted(query, page, items_per_page, sort)?;
         let start = (page - 1) * items_per_page;
+        self.search_fetched_at
+            .entry(key.clone())
+            .or_insert_with(Instant::now);
         let (total, page_cache) = self.search_cache.entry(key).or_insert((0, HashMap::new()));
         for (ind, item) in resp.crates.into_iter().enumerate() {
             page_cache.insert(start + ind as u32, item);
@@ -167,68 +833,88 @@ impl CrateSearcher {
 
     /// Searches the query, defaulting to data available in the cache. If not cached, cache is updated
     /// to include new values. May fetch more items at once to prevent excessive API requests.
-    pub fn search_sorted_with_cache<T: AsRef<str>>(
+    pub fn search_sorted_with_cache(
         &mut self,
-        term: T,
+        query: &SearchQuery,
         page: u32,
         items_per_page: u32,
         sort: &CratesSort,
     ) -> Result<(u32, Vec<&CrateSearch>), reqwest::Error> {
-        let key = (term.as_ref().to_string(), sort.to_sort_string());
+        self.expire_search(query, sort);
+        let key = (query.clone(), sort.to_sort_string());
         if !self.search_cache.contains_key(&key) {
-            self.search_and_add_to_cache(
-                term.as_ref(),
-                ceil_div(page, 10),
-                10 * items_per_page,
-                sort,
-            )?;
+            self.search_and_add_to_cache(query, ceil_div(page, 10), 10 * items_per_page, sort)?;
             return Ok(self
-                .search_sorted_cached(term.as_ref(), page, items_per_page, sort)
+                .search_sorted_cached(query, page, items_per_page, sort)
                 .unwrap());
         }
 
-        if self.check_page_cached(term.as_ref(), page, items_per_page, sort) {
+        if self.check_page_cached(query, page, items_per_page, sort) {
             return Ok(self
-                .search_sorted_cached(term.as_ref(), page, items_per_page, sort)
+                .search_sorted_cached(query, page, items_per_page, sort)
                 .unwrap());
         }
 
         // This thrashes the cache if the page size changes between calls.
-        self.search_and_add_to_cache(term.as_ref(), ceil_div(page, 10), 10 * items_per_page, sort)?;
+        self.search_and_add_to_cache(query, ceil_div(page, 10), 10 * items_per_page, sort)?;
         let (total_num, page_cache) = self.search_cache.get(&key).unwrap();
         let res = get_all_items(page_cache, page, items_per_page, *total_num).unwrap();
         Ok((*total_num, res))
     }
 
+    /// Gets the results from the first one up to the first that isn't cached, along with the
+    /// total number of results.  Used to page through results that are filtered on the client.
+    pub fn search_prefix_cached(
+        &self,
+        query: &SearchQuery,
+        sort: &CratesSort,
+    ) -> Option<(u32, Vec<&CrateSearch>)> {
+        let key = (query.clone(), sort.to_sort_string());
+        let (total_num, page_cache) = self.search_cache.get(&key)?;
+        let crates = (0..*total_num)
+            .map_while(|index| page_cache.get(&index))
+            .collect();
+        Some((*total_num, crates))
+    }
+
+    /// Fetches the results following the index into the cache, returning how many were requested
+    pub fn fetch_ahead(
+        &mut self,
+        query: &SearchQuery,
+        index: u32,
+        sort: &CratesSort,
+    ) -> Result<u32, reqwest::Error> {
+        self.search_and_add_to_cache(query, index / FETCH_AHEAD + 1, FETCH_AHEAD, sort)?;
+        Ok(FETCH_AHEAD)
+    }
+
     /// Checks if the given page is cached.
-    fn check_page_cached<T: AsRef<str>>(
+    fn check_page_cached(
         &self,
-        term: T,
+        query: &SearchQuery,
         page: u32,
         items_per_page: u32,
         sort: &CratesSort,
     ) -> bool {
-        let key = (term.as_ref().to_string(), sort.to_sort_string());
+        let key = (query.clone(), sort.to_sort_string());
         if let Some((total_num, page_cache)) = self.search_cache.get(&key) {
             let start = (page - 1) * items_per_page;
             let end = page * items_per_page;
-            (start..end.min(*total_num))
-                .map(|x| page_cache.get(&x).is_some())
-                .all(|x| x)
+            (start..end.min(*total_num)).all(|x| page_cache.contains_key(&x))
         } else {
             false
         }
     }
 
     /// Searches the query and associated pages from the internal cache.
-    pub fn search_sorted_cached<T: AsRef<str>>(
+    pub fn search_sorted_cached(
         &self,
-        term: T,
+        query: &SearchQuery,
         page: u32,
         items_per_page: u32,
         sort: &CratesSort,
     ) -> Option<(u32, Vec<&CrateSearch>)> {
-        let key = (term.as_ref().to_string(), sort.to_sort_string());
+        let key = (query.clone(), sort.to_sort_string());
         let (total_num, page_cache) = self.search_cache.get(&key)?;
         Some((
             *total_num,
@@ -237,35 +923,505 @@ impl CrateSearcher {
     }
 
     /// Searches the query without any caching.
-    pub fn search_sorted<T: AsRef<str>>(
+    pub fn search_sorted(
         &self,
-        term: T,
+        query: &SearchQuery,
         page: u32,
         items_per_page: u32,
         sort: &CratesSort,
     ) -> Result<CrateSearchResponse, reqwest::Error> {
-        self.search_sorted_count(term, page, items_per_page, sort)
+        self.search_sorted_count(query, page, items_per_page, sort)
     }
 
-    pub fn search_sorted_count<T: AsRef<str>>(
+    pub fn search_sorted_count(
         &self,
-        term: T,
+        query: &SearchQuery,
         page: u32,
         items_per_page: u32,
         sort: &CratesSort,
     ) -> Result<CrateSearchResponse, reqwest::Error> {
         // https://crates.io/api/v1/crates?page=1&per_page=10&q=serde
-        let mut url = Url::parse(CRATES_URL).unwrap();
-        let url = url
-            .query_pairs_mut()
+        let mut url = Url::parse(&self.url("crates")).unwrap();
+        let mut pairs = url.query_pairs_mut();
+        pairs
             .append_pair("page", page.to_string().as_str())
             .append_pair("per_page", items_per_page.to_string().as_str())
-            .append_pair("q", term.as_ref())
-            .append_pair("sort", sort.to_sort_string().as_str())
-            .finish();
+            .append_pair("q", query.term.as_str())
+            .append_pair("sort", sort.to_sort_string().as_str());
+        if let Some(category) = &query.category {
+            pairs.append_pair("category", category);
+        }
+        if let Some(keyword) = &query.keyword {
+            pairs.append_pair("keyword", keyword);
+        }
+        if !query.all_keywords.is_empty() {
+            pairs.append_pair("all_keywords", &query.all_keywords.join(" "));
+        }
+        if let Some(login) = &query.user {
+            let user_id = match self.user_cache.get(login) {
+                Some(user_id) => *user_id,
+                None => self.get_user(login)?.user.id,
+            };
+            pairs.append_pair("user_id", &user_id.to_string());
+        }
+        let url = pairs.finish();
 
         let req = self.client.get(url.as_str()).build()?;
-        let resp = self.client.execute(req)?;
-        Ok(resp.json::<CrateSearchResponse>()?)
+        let resp = self.client.execute(req)?.error_for_status()?;
+        resp.json::<CrateSearchResponse>()
+    }
+
+    /// Gets the page of results that follows a search, from the `next_page` of the previous
+    /// page's response.  It carries the whole query, so crates.io can page by position.
+    pub fn search_next_page(&self, next_page: &str) -> Result<CrateSearchResponse, reqwest::Error> {
+        // https://crates.io/api/v1/crates?page=2&per_page=100&q=serde
+        let url = format!("{}{}", self.url("crates"), next_page);
+        self.client
+            .get(url.as_str())
+            .send()?
+            .error_for_status()?
+            .json::<CrateSearchResponse>()
+    }
+}
+
+impl CrateSearcher {
+    /// Gets the crate and its versions, defaulting to data available in the cache.
+    pub fn get_crate_with_cache(&mut self, name: &str) -> Result<&CrateResponse, reqwest::Error> {
+        if self.is_stale(self.crate_fetched_at.get(name)) {
+            self.crate_cache.remove(name);
+        }
+        if !self.crate_cache.contains_key(name) {
+            let resp = self.get_crate(name)?;
+            self.crate_cache.insert(name.to_string(), resp);
+            self.crate_fetched_at
+                .insert(name.to_string(), Instant::now());
+        }
+        Ok(self.crate_cache.get(name).unwrap())
+    }
+
+    /// Puts the crate in the internal cache, as if it had been fetched
+    #[cfg(test)]
+    pub fn cache_crate(&mut self, resp: CrateResponse) {
+        self.crate_cache.insert(resp.crte.name.clone(), resp);
+    }
+
+    /// Gets the crate from the internal cache.
+    pub fn get_crate_cached(&self, name: &str) -> Option<&CrateResponse> {
+        self.crate_cache.get(name)
+    }
+
+    /// Gets the crate and its versions without any caching.
+    pub fn get_crate(&self, name: &str) -> Result<CrateResponse, reqwest::Error> {
+        // https://crates.io/api/v1/crates/serde
+        let url = format!("{}/{}", self.url("crates"), name);
+        self.client
+            .get(url.as_str())
+            .send()?
+            .error_for_status()?
+            .json::<CrateResponse>()
+    }
+
+    /// Gets a version of the crate, defaulting to data available in the cache.
+    pub fn get_version_with_cache(
+        &mut self,
+        name: &str,
+        version: &str,
+    ) -> Result<&CrateVersion, reqwest::Error> {
+        let key = (name.to_string(), version.to_string());
+        if !self.version_cache.contains_key(&key) {
+            let resp = self.get_version(name, version)?;
+            self.version_cache.insert(key.clone(), resp.version);
+        }
+        Ok(self.version_cache.get(&key).unwrap())
+    }
+
+    /// Gets a version of the crate from the internal cache.
+    pub fn get_version_cached(&self, name: &str, version: &str) -> Option<&CrateVersion> {
+        self.version_cache
+            .get(&(name.to_string(), version.to_string()))
+    }
+
+    /// Gets a version of the crate without any caching.
+    pub fn get_version(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<VersionResponse, reqwest::Error> {
+        // https://crates.io/api/v1/crates/serde/1.0.0
+        let url = format!("{}/{}/{}", self.url("crates"), name, version);
+        self.client
+            .get(url.as_str())
+            .send()?
+            .error_for_status()?
+            .json::<VersionResponse>()
+    }
+
+    /// Gets the dependencies of a version of the crate, defaulting to data available in the cache.
+    pub fn get_dependencies_with_cache(
+        &mut self,
+        name: &str,
+        version: &str,
+    ) -> Result<&Vec<Dependency>, reqwest::Error> {
+        let key = (name.to_string(), version.to_string());
+        if !self.dependency_cache.contains_key(&key) {
+            let resp = self.get_dependencies(name, version)?;
+            self.dependency_cache.insert(key.clone(), resp.dependencies);
+        }
+        Ok(self.dependency_cache.get(&key).unwrap())
+    }
+
+    /// Gets the dependencies of a version of the crate from the internal cache.
+    pub fn get_dependencies_cached(&self, name: &str, version: &str) -> Option<&Vec<Dependency>> {
+        self.dependency_cache
+            .get(&(name.to_string(), version.to_string()))
+    }
+
+    /// Gets the dependencies of a version of the crate without any caching.
+    pub fn get_dependencies(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<DependenciesResponse, reqwest::Error> {
+        // https://crates.io/api/v1/crates/serde/1.0.0/dependencies
+        let url = format!("{}/{}/{}/dependencies", self.url("crates"), name, version);
+        self.client
+            .get(url.as_str())
+            .send()?
+            .error_for_status()?
+            .json::<DependenciesResponse>()
+    }
+
+    /// Gets a page of the crates that depend on the crate without any caching, the most
+    /// downloaded first.
+    pub fn get_reverse_dependencies(
+        &self,
+        name: &str,
+        page: u32,
+        items_per_page: u32,
+    ) -> Result<ReverseDependenciesResponse, reqwest::Error> {
+        // https://crates.io/api/v1/crates/serde/reverse_dependencies?page=1&per_page=10
+        let url = format!(
+            "{}/{}/reverse_dependencies?page={}&per_page={}",
+            self.url("crates"),
+            name,
+            page,
+            items_per_page
+        );
+        self.client
+            .get(url.as_str())
+            .send()?
+            .error_for_status()?
+            .json::<ReverseDependenciesResponse>()
+    }
+
+    /// Gets the owners of the crate, defaulting to data available in the cache.
+    pub fn get_owners_with_cache(&mut self, name: &str) -> Result<&Vec<Owner>, reqwest::Error> {
+        if !self.owner_cache.contains_key(name) {
+            let resp = self.get_owners(name)?;
+            self.owner_cache.insert(name.to_string(), resp.users);
+        }
+        Ok(self.owner_cache.get(name).unwrap())
+    }
+
+    /// Gets the owners of the crate from the internal cache.
+    pub fn get_owners_cached(&self, name: &str) -> Option<&Vec<Owner>> {
+        self.owner_cache.get(name)
+    }
+
+    /// Gets the owners of the crate without any caching.
+    pub fn get_owners(&self, name: &str) -> Result<OwnersResponse, reqwest::Error> {
+        // https://crates.io/api/v1/crates/serde/owners
+        let url = format!("{}/{}/owners", self.url("crates"), name);
+        self.client
+            .get(url.as_str())
+            .send()?
+            .error_for_status()?
+            .json::<OwnersResponse>()
+    }
+}
+
+impl CrateSearcher {
+    /// Gets the README of a version of the crate, defaulting to data available in the cache.
+    pub fn get_readme_with_cache(
+        &mut self,
+        name: &str,
+        version: &str,
+    ) -> Result<&String, reqwest::Error> {
+        let key = (name.to_string(), version.to_string());
+        if !self.readme_cache.contains_key(&key) {
+            let readme = self.get_readme(name, version)?;
+            self.readme_cache.insert(key.clone(), readme);
+        }
+        Ok(self.readme_cache.get(&key).unwrap())
+    }
+
+    /// Gets the README of a version of the crate from the internal cache.
+    pub fn get_readme_cached(&self, name: &str, version: &str) -> Option<&String> {
+        self.readme_cache
+            .get(&(name.to_string(), version.to_string()))
+    }
+
+    /// Gets the README of a version of the crate without any caching.  crates.io serves the
+    /// README rendered as HTML.
+    pub fn get_readme(&self, name: &str, version: &str) -> Result<String, reqwest::Error> {
+        // https://crates.io/api/v1/crates/serde/1.0.0/readme
+        let url = format!("{}/{}/{}/readme", self.url("crates"), name, version);
+        self.client
+            .get(url.as_str())
+            .send()?
+            .error_for_status()?
+            .text()
+    }
+}
+
+impl CrateSearcher {
+    /// Gets the top-level categories, defaulting to data available in the cache.
+    pub fn get_categories_with_cache(&mut self) -> Result<&Vec<Category>, reqwest::Error> {
+        if self.categories_cache.is_none() {
+            self.categories_cache = Some(self.get_categories()?);
+        }
+        Ok(self.categories_cache.as_ref().unwrap())
+    }
+
+    /// Gets the top-level categories from the internal cache.
+    pub fn get_categories_cached(&self) -> Option<&Vec<Category>> {
+        self.categories_cache.as_ref()
+    }
+
+    /// Gets every top-level category without any caching.
+    pub fn get_categories(&self) -> Result<Vec<Category>, reqwest::Error> {
+        let mut categories = Vec::new();
+        for page in 1.. {
+            // https://crates.io/api/v1/categories?page=1&per_page=100&sort=alpha
+            let url = format!(
+                "{}?page={}&per_page=100&sort=alpha",
+                self.url("categories"),
+                page
+            );
+            let resp = self
+                .client
+                .get(url.as_str())
+                .send()?
+                .error_for_status()?
+                .json::<CategoriesResponse>()?;
+            let done = resp.categories.is_empty();
+            categories.extend(resp.categories);
+            if done || categories.len() >= resp.meta.total as usize {
+                break;
+            }
+        }
+        Ok(categories)
+    }
+
+    /// Gets the subcategories of the category, defaulting to data available in the cache.
+    pub fn get_subcategories_with_cache(
+        &mut self,
+        slug: &str,
+    ) -> Result<&Vec<Category>, reqwest::Error> {
+        if !self.subcategory_cache.contains_key(slug) {
+            let resp = self.get_category(slug)?;
+            self.subcategory_cache
+                .insert(slug.to_string(), resp.category.subcategories);
+        }
+        Ok(self.subcategory_cache.get(slug).unwrap())
+    }
+
+    /// Gets the subcategories of the category from the internal cache.
+    pub fn get_subcategories_cached(&self, slug: &str) -> Option<&Vec<Category>> {
+        self.subcategory_cache.get(slug)
+    }
+
+    /// Gets the category and its subcategories without any caching.
+    pub fn get_category(&self, slug: &str) -> Result<CategoryResponse, reqwest::Error> {
+        // https://crates.io/api/v1/categories/web-programming
+        let url = format!("{}/{}", self.url("categories"), slug);
+        self.client
+            .get(url.as_str())
+            .send()?
+            .error_for_status()?
+            .json::<CategoryResponse>()
+    }
+
+    /// Gets the most used keywords, defaulting to data available in the cache.
+    pub fn get_keywords_with_cache(&mut self) -> Result<&Vec<Keyword>, reqwest::Error> {
+        if self.keyword_cache.is_none() {
+            self.keyword_cache = Some(self.get_keywords()?.keywords);
+        }
+        Ok(self.keyword_cache.as_ref().unwrap())
+    }
+
+    /// Gets the most used keywords from the internal cache.
+    pub fn get_keywords_cached(&self) -> Option<&Vec<Keyword>> {
+        self.keyword_cache.as_ref()
+    }
+
+    /// Gets the ID of the user with the login, defaulting to data available in the cache.
+    pub fn get_user_id_with_cache(&mut self, login: &str) -> Result<u64, reqwest::Error> {
+        if let Some(user_id) = self.user_cache.get(login) {
+            return Ok(*user_id);
+        }
+        let user_id = self.get_user(login)?.user.id;
+        self.user_cache.insert(login.to_string(), user_id);
+        Ok(user_id)
+    }
+
+    /// Gets the user with the login without any caching.  Fails with a 404 for an unknown login.
+    pub fn get_user(&self, login: &str) -> Result<UserResponse, reqwest::Error> {
+        // https://crates.io/api/v1/users/dtolnay
+        let url = format!("{}/{}", self.url("users"), login);
+        self.client
+            .get(url.as_str())
+            .send()?
+            .error_for_status()?
+            .json::<UserResponse>()
+    }
+
+    /// Gets the 100 most used keywords without any caching.
+    pub fn get_keywords(&self) -> Result<KeywordsResponse, reqwest::Error> {
+        // https://crates.io/api/v1/keywords?page=1&per_page=100&sort=crates
+        let url = format!("{}?page=1&per_page=100&sort=crates", self.url("keywords"));
+        self.client
+            .get(url.as_str())
+            .send()?
+            .error_for_status()?
+            .json::<KeywordsResponse>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sort_names_parse() {
+        for name in CratesSort::NAMES {
+            assert!(name.parse::<CratesSort>().is_ok(), "{}", name);
+        }
+        assert_eq!(
+            "all time downloads".parse::<CratesSort>(),
+            Ok(CratesSort::AllTimeDownload)
+        );
+        assert_eq!(
+            "Recent_Update".parse::<CratesSort>(),
+            Ok(CratesSort::RecentUpdate)
+        );
+        assert_eq!(" NEWEST ".parse::<CratesSort>(), Ok(CratesSort::NewlyAdded));
+        assert!("popular".parse::<CratesSort>().is_err());
+    }
+
+    #[test]
+    fn sort_names_round_trip() {
+        let sorts = [
+            CratesSort::Relevance,
+            CratesSort::AllTimeDownload,
+            CratesSort::RecentDownload,
+            CratesSort::RecentUpdate,
+            CratesSort::NewlyAdded,
+        ];
+        for sort in sorts {
+            assert_eq!(sort.to_sort_string().parse(), Ok(sort.clone()));
+            assert_eq!(sort.to_string().parse(), Ok(sort));
+        }
+    }
+
+    fn query(s: &str) -> SearchQuery {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn qualifiers() {
+        let q = query("serde category:encoding keyword:json user:dtolnay fast");
+        assert_eq!(q.term, "serde fast");
+        assert_eq!(q.category.as_deref(), Some("encoding"));
+        assert_eq!(q.keyword.as_deref(), Some("json"));
+        assert_eq!(q.user.as_deref(), Some("dtolnay"));
+        assert_eq!(
+            query("Category:parsing").category.as_deref(),
+            Some("parsing")
+        );
+    }
+
+    #[test]
+    fn download_bounds() {
+        assert_eq!(
+            query("downloads:>1m").downloads,
+            Some(Bound::Above(1_000_000))
+        );
+        assert_eq!(
+            query("downloads:>=10k").downloads,
+            Some(Bound::AtLeast(10_000))
+        );
+        assert_eq!(
+            query("downloads:<1.5m").downloads,
+            Some(Bound::Below(1_500_000))
+        );
+        assert_eq!(
+            query("downloads:<=2b").downloads,
+            Some(Bound::AtMost(2_000_000_000))
+        );
+        assert!("downloads:1m".parse::<SearchQuery>().is_err());
+        assert!("downloads:>lots".parse::<SearchQuery>().is_err());
+        assert!("downloads:>-1".parse::<SearchQuery>().is_err());
+    }
+
+    #[test]
+    fn updated_bounds() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        assert_eq!(
+            query("updated:>2024-01-31").updated,
+            Some(Bound::Above(date))
+        );
+        assert_eq!(
+            query("updated:<=2024-01-31").updated,
+            Some(Bound::AtMost(date))
+        );
+        assert!("updated:>2024-13-01".parse::<SearchQuery>().is_err());
+        assert!("updated:>yesterday".parse::<SearchQuery>().is_err());
+
+        let bound = Bound::AtLeast(date);
+        assert!(bound.contains(&date));
+        assert!(!bound.contains(&date.pred_opt().unwrap()));
+    }
+
+    #[test]
+    fn all_keywords() {
+        let q = query("all_keywords:async,tokio all_keywords:,net,");
+        assert_eq!(q.all_keywords, ["async", "tokio", "net"]);
+    }
+
+    #[test]
+    fn invalid_qualifiers() {
+        assert!("category:".parse::<SearchQuery>().is_err());
+        assert!("user:a user:b".parse::<SearchQuery>().is_err());
+    }
+
+    #[test]
+    fn unknown_qualifiers() {
+        for s in ["license:mit serde", "catgory:parsing", "Foo_Bar:x"] {
+            let err = s.parse::<SearchQuery>().unwrap_err();
+            assert!(err.starts_with("Unknown qualifier"), "{}", err);
+            assert!(
+                err.contains("category:, keyword:, all_keywords:"),
+                "{}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn urls_and_paths_are_text() {
+        assert_eq!(query("http://example.com").term, "http://example.com");
+        assert_eq!(query("https://docs.rs/serde").term, "https://docs.rs/serde");
+        assert_eq!(query("foo::bar").term, "foo::bar");
+        assert_eq!(query("keyword::bar").term, "keyword::bar");
+        assert_eq!(query("v1:2 12:30").term, "v1:2 12:30");
+    }
+
+    #[test]
+    fn query_round_trips() {
+        let s = "category:encoding all_keywords:async,tokio updated:>=2024-01-01 downloads:<10k \
+                 serde";
+        assert_eq!(query(s).to_string(), s);
+        assert_eq!(query(&query(s).to_string()), query(s));
     }
 }
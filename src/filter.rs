@@ -0,0 +1,325 @@
+use chrono::{Duration, Local};
+
+use crate::crates_io::CrateSearch;
+
+/// The choices for the minimum downloads, with how each is shown
+const DOWNLOAD_STEPS: [(u64, &str); 6] = [
+    (0, "any"),
+    (1_000, "1k"),
+    (10_000, "10k"),
+    (100_000, "100k"),
+    (1_000_000, "1M"),
+    (10_000_000, "10M"),
+];
+
+/// The choices for how many months ago a crate was last updated.  0 allows any time.
+const MONTH_STEPS: [u32; 7] = [0, 1, 3, 6, 12, 24, 36];
+
+/// An option in the filter panel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterOption {
+    MinDownloads,
+    MinRecentDownloads,
+    UpdatedWithin,
+    ExcludeYanked,
+    HasRepository,
+    HasDocumentation,
+    ExactMatch,
+}
+
+impl FilterOption {
+    pub const ALL: [FilterOption; 7] = [
+        FilterOption::MinDownloads,
+        FilterOption::MinRecentDownloads,
+        FilterOption::UpdatedWithin,
+        FilterOption::ExcludeYanked,
+        FilterOption::HasRepository,
+        FilterOption::HasDocumentation,
+        FilterOption::ExactMatch,
+    ];
+}
+
+/// Filters applied to the search results on top of the crates.io query
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchFilter {
+    pub min_downloads: u64,
+    pub min_recent_downloads: u64,
+    /// Only crates updated within this many months.  0 allows any time.
+    pub updated_within: u32,
+    /// Hides crates whose every version has been yanked
+    pub exclude_yanked: bool,
+    pub has_repository: bool,
+    pub has_documentation: bool,
+    /// Only the crate whose name matches the search term
+    pub exact_match: bool,
+}
+
+impl SearchFilter {
+    /// Whether any of the filters would hide a crate
+    pub fn is_active(&self) -> bool {
+        *self != Self::default()
+    }
+
+    pub fn matches(&self, crte: &CrateSearch) -> bool {
+        let updated = self.updated_within == 0
+            || crte.updated_at > Local::now() - Duration::days(30 * self.updated_within as i64);
+        crte.downloads >= self.min_downloads
+            && crte.recent_downloads >= self.min_recent_downloads
+            && updated
+            && (!self.exclude_yanked || !crte.yanked)
+            && (!self.has_repository || crte.repository.is_some())
+            && (!self.has_documentation || crte.documentation.is_some())
+            && (!self.exact_match || crte.exact_match)
+    }
+
+    /// Changes the option to its next choice, or its previous one when going back.  Flags are
+    /// toggled either way.
+    pub fn step(&mut self, option: FilterOption, forward: bool) {
+        match option {
+            FilterOption::MinDownloads => {
+                self.min_downloads = step_downloads(self.min_downloads, forward)
+            }
+            FilterOption::MinRecentDownloads => {
+                self.min_recent_downloads = step_downloads(self.min_recent_downloads, forward)
+            }
+            FilterOption::UpdatedWithin => {
+                let pos = MONTH_STEPS
+                    .iter()
+                    .position(|months| *months == self.updated_within)
+                    .unwrap_or(0);
+                self.updated_within = MONTH_STEPS[step_index(pos, MONTH_STEPS.len(), forward)];
+            }
+            FilterOption::ExcludeYanked => self.exclude_yanked = !self.exclude_yanked,
+            FilterOption::HasRepository => self.has_repository = !self.has_repository,
+            FilterOption::HasDocumentation => self.has_documentation = !self.has_documentation,
+            FilterOption::ExactMatch => self.exact_match = !self.exact_match,
+        }
+    }
+
+    /// The option and its current choice, as shown in the filter panel
+    pub fn describe(&self, option: FilterOption) -> String {
+        let flag = |enabled: bool| if enabled { "[x]" } else { "[ ]" };
+        match option {
+            FilterOption::MinDownloads => {
+                format!(
+                    "{:<28}{}",
+                    "Minimum downloads",
+                    downloads_label(self.min_downloads)
+                )
+            }
+            FilterOption::MinRecentDownloads => format!(
+                "{:<28}{}",
+                "Minimum recent downloads",
+                downloads_label(self.min_recent_downloads)
+            ),
+            FilterOption::UpdatedWithin => {
+                let months = match self.updated_within {
+                    0 => "any time".to_string(),
+                    1 => "1 month".to_string(),
+                    n => format!("{} months", n),
+                };
+                format!("{:<28}{}", "Updated within", months)
+            }
+            FilterOption::ExcludeYanked => format!("{} Exclude yanked", flag(self.exclude_yanked)),
+            FilterOption::HasRepository => {
+                format!("{} Has a repository link", flag(self.has_repository))
+            }
+            FilterOption::HasDocumentation => {
+                format!("{} Has documentation", flag(self.has_documentation))
+            }
+            FilterOption::ExactMatch => format!("{} Exact match only", flag(self.exact_match)),
+        }
+    }
+}
+
+fn step_index(pos: usize, len: usize, forward: bool) -> usize {
+    if forward {
+        (pos + 1) % len
+    } else {
+        (pos + len - 1) % len
+    }
+}
+
+fn step_downloads(downloads: u64, forward: bool) -> u64 {
+    let pos = DOWNLOAD_STEPS
+        .iter()
+        .position(|(step, _)| *step == downloads)
+        .unwrap_or(0);
+    DOWNLOAD_STEPS[step_index(pos, DOWNLOAD_STEPS.len(), forward)].0
+}
+
+fn downloads_label(downloads: u64) -> String {
+    DOWNLOAD_STEPS
+        .iter()
+        .find(|(step, _)| *step == downloads)
+        .map_or_else(|| downloads.to_string(), |(_, label)| label.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crates_io::CrateSearchLinks;
+
+    fn crte(downloads: u64, recent_downloads: u64, months_ago: i64) -> CrateSearch {
+        let updated_at = Local::now() - Duration::days(30 * months_ago + 1);
+        CrateSearch {
+            id: "serde".into(),
+            name: "serde".into(),
+            updated_at,
+            created_at: updated_at,
+            downloads,
+            recent_downloads,
+            max_version: "1.0.0".into(),
+            newest_version: "1.0.0".into(),
+            description: None,
+            documentation: None,
+            repository: None,
+            homepage: None,
+            links: CrateSearchLinks {
+                version_downloads: String::new(),
+                versions: None,
+                owners: String::new(),
+                owner_team: String::new(),
+                owner_user: String::new(),
+                reverse_dependencies: String::new(),
+            },
+            exact_match: false,
+            yanked: false,
+        }
+    }
+
+    #[test]
+    fn default_matches_everything() {
+        let filter = SearchFilter::default();
+        assert!(!filter.is_active());
+        let mut yanked = crte(0, 0, 120);
+        yanked.yanked = true;
+        assert!(filter.matches(&yanked));
+    }
+
+    #[test]
+    fn minimum_downloads() {
+        let filter = SearchFilter {
+            min_downloads: 1_000,
+            min_recent_downloads: 100,
+            ..SearchFilter::default()
+        };
+        assert!(filter.is_active());
+        assert!(filter.matches(&crte(1_000, 100, 0)));
+        assert!(!filter.matches(&crte(999, 100, 0)));
+        assert!(!filter.matches(&crte(1_000, 99, 0)));
+    }
+
+    #[test]
+    fn updated_within() {
+        let filter = SearchFilter {
+            updated_within: 6,
+            ..SearchFilter::default()
+        };
+        assert!(filter.matches(&crte(0, 0, 5)));
+        assert!(!filter.matches(&crte(0, 0, 6)));
+    }
+
+    #[test]
+    fn flags() {
+        let mut crte = crte(0, 0, 0);
+        let filters = [
+            SearchFilter {
+                exclude_yanked: true,
+                ..SearchFilter::default()
+            },
+            SearchFilter {
+                has_repository: true,
+                ..SearchFilter::default()
+            },
+            SearchFilter {
+                has_documentation: true,
+                ..SearchFilter::default()
+            },
+            SearchFilter {
+                exact_match: true,
+                ..SearchFilter::default()
+            },
+        ];
+        crte.yanked = true;
+        assert!(filters.iter().all(|filter| !filter.matches(&crte)));
+
+        crte.yanked = false;
+        crte.repository = Some("https://github.com/serde-rs/serde".into());
+        crte.documentation = Some("https://docs.rs/serde".into());
+        crte.exact_match = true;
+        assert!(filters.iter().all(|filter| filter.matches(&crte)));
+    }
+
+    #[test]
+    fn step_wraps_around() {
+        let mut filter = SearchFilter::default();
+        filter.step(FilterOption::MinDownloads, false);
+        assert_eq!(filter.min_downloads, 10_000_000);
+        filter.step(FilterOption::MinDownloads, true);
+        assert_eq!(filter.min_downloads, 0);
+
+        for _ in 0..MONTH_STEPS.len() - 1 {
+            filter.step(FilterOption::UpdatedWithin, true);
+        }
+        assert_eq!(filter.updated_within, 36);
+        filter.step(FilterOption::UpdatedWithin, true);
+        assert_eq!(filter.updated_within, 0);
+        filter.step(FilterOption::UpdatedWithin, false);
+        assert_eq!(filter.updated_within, 36);
+    }
+
+    #[test]
+    fn step_from_unlisted_value_starts_over() {
+        let mut filter = SearchFilter {
+            min_recent_downloads: 5,
+            ..SearchFilter::default()
+        };
+        filter.step(FilterOption::MinRecentDownloads, true);
+        assert_eq!(filter.min_recent_downloads, 1_000);
+    }
+
+    #[test]
+    fn step_toggles_flags_both_ways() {
+        let mut filter = SearchFilter::default();
+        for option in &FilterOption::ALL[3..] {
+            filter.step(*option, true);
+        }
+        assert!(
+            filter.exclude_yanked
+                && filter.has_repository
+                && filter.has_documentation
+                && filter.exact_match
+        );
+        for option in &FilterOption::ALL[3..] {
+            filter.step(*option, false);
+        }
+        assert_eq!(filter, SearchFilter::default());
+    }
+
+    #[test]
+    fn describe() {
+        let filter = SearchFilter {
+            min_downloads: 100_000,
+            updated_within: 1,
+            exact_match: true,
+            ..SearchFilter::default()
+        };
+        assert_eq!(
+            filter.describe(FilterOption::MinDownloads),
+            "Minimum downloads           100k"
+        );
+        assert_eq!(
+            filter.describe(FilterOption::UpdatedWithin),
+            "Updated within              1 month"
+        );
+        assert_eq!(
+            filter.describe(FilterOption::ExactMatch),
+            "[x] Exact match only"
+        );
+        assert_eq!(
+            filter.describe(FilterOption::HasRepository),
+            "[ ] Has a repository link"
+        );
+    }
+}
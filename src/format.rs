@@ -0,0 +1,292 @@
+use std::{env, str::FromStr, sync::OnceLock};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// The format dates are shown in unless the config says otherwise
+pub const DEFAULT_DATE_FORMAT: &str = "%x %H:%M";
+
+/// How dates and counts are shown, set from the config when the app starts
+static FORMATTING: OnceLock<Formatting> = OnceLock::new();
+/// The separators of the user's locale, read from the environment the first time they're needed
+static SEPARATORS: OnceLock<Separators> = OnceLock::new();
+
+/// How dates and counts are shown in the TUI and printed tables
+#[derive(Debug)]
+pub struct Formatting {
+    pub date_style: DateStyle,
+    /// The chrono format string used by [`DateStyle::Format`]
+    pub date_format: String,
+    pub count_style: CountStyle,
+}
+
+impl Default for Formatting {
+    fn default() -> Self {
+        Self {
+            date_style: DateStyle::default(),
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            count_style: CountStyle::default(),
+        }
+    }
+}
+
+impl Formatting {
+    /// Sets how dates and counts are shown.  Only the first call has an effect.
+    pub fn init(formatting: Formatting) {
+        let _ = FORMATTING.set(formatting);
+    }
+}
+
+fn formatting() -> &'static Formatting {
+    FORMATTING.get_or_init(Formatting::default)
+}
+
+/// The ways of showing a date
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DateStyle {
+    /// With the date format from the config
+    #[default]
+    Format,
+    /// As an ISO 8601 date, e.g. 2024-01-31
+    Iso,
+    /// Relative to now, e.g. 3 days ago
+    Relative,
+}
+
+impl DateStyle {
+    pub const NAMES: [&'static str; 3] = ["format", "iso", "relative"];
+}
+
+impl FromStr for DateStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "format" => Ok(Self::Format),
+            "iso" => Ok(Self::Iso),
+            "relative" => Ok(Self::Relative),
+            _ => Err(format!(
+                "Unknown date style {}, expected one of {}",
+                s,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// The ways of showing a count, such as the downloads of a crate
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CountStyle {
+    /// The digits alone, e.g. 184729384
+    Plain,
+    /// With thousands separators, e.g. 184,729,384
+    #[default]
+    Separated,
+    /// Rounded with a suffix, e.g. 184.7M
+    Humanized,
+}
+
+impl CountStyle {
+    pub const NAMES: [&'static str; 3] = ["plain", "separated", "humanized"];
+}
+
+impl FromStr for CountStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "plain" => Ok(Self::Plain),
+            "separated" => Ok(Self::Separated),
+            "humanized" => Ok(Self::Humanized),
+            _ => Err(format!(
+                "Unknown count style {}, expected one of {}",
+                s,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Shows the date in the configured style
+pub fn date(date: &DateTime<Local>) -> String {
+    let formatting = formatting();
+    match formatting.date_style {
+        DateStyle::Format => date.format(&formatting.date_format).to_string(),
+        DateStyle::Iso => iso_date(date),
+        DateStyle::Relative => relative_time(date, &Local::now()),
+    }
+}
+
+/// Shows the count in the configured style
+pub fn count(count: u64) -> String {
+    match formatting().count_style {
+        CountStyle::Plain => count.to_string(),
+        CountStyle::Separated => separated_count(count),
+        CountStyle::Humanized => humanized_count(count),
+    }
+}
+
+/// The date as an ISO 8601 date, e.g. 2024-01-31
+pub fn iso_date(date: &DateTime<Local>) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+/// How long before or after `now` the date is, e.g. `3 days ago` or `in 2 hours`
+pub fn relative_time(date: &DateTime<Local>, now: &DateTime<Local>) -> String {
+    let seconds = now.signed_duration_since(*date).num_seconds();
+    let elapsed = seconds.unsigned_abs();
+    let (amount, unit) = match elapsed {
+        0..=59 => return "just now".to_string(),
+        60..=3_599 => (elapsed / 60, "minute"),
+        3_600..=86_399 => (elapsed / 3_600, "hour"),
+        86_400..=2_591_999 => (elapsed / 86_400, "day"),
+        2_592_000..=31_535_999 => (elapsed / 2_592_000, "month"),
+        _ => (elapsed / 31_536_000, "year"),
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+    if seconds < 0 {
+        format!("in {} {}{}", amount, unit, plural)
+    } else {
+        format!("{} {}{} ago", amount, unit, plural)
+    }
+}
+
+/// The count with the locale's thousands separator, e.g. `184,729,384`
+pub fn separated_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut separated = String::with_capacity(digits.len() * 4 / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            separated.push(separators().thousands);
+        }
+        separated.push(digit);
+    }
+    separated
+}
+
+/// The count rounded to one decimal with a suffix, e.g. `184.7M`.  Counts below a thousand are
+/// shown as they are.
+pub fn humanized_count(count: u64) -> String {
+    const UNITS: [(f64, &str); 3] = [(1e9, "B"), (1e6, "M"), (1e3, "K")];
+    let unit = match UNITS.iter().position(|(size, _)| count as f64 >= *size) {
+        Some(unit) => unit,
+        None => return count.to_string(),
+    };
+    let count = count as f64;
+    // a count that rounds up to the next unit, such as 999,990, is shown in that unit
+    let (size, suffix) = match unit {
+        0 => UNITS[0],
+        unit if (count / UNITS[unit].0 * 10.0).round() >= 10_000.0 => UNITS[unit - 1],
+        unit => UNITS[unit],
+    };
+    let rounded = format!("{:.1}", count / size);
+    let rounded = rounded.strip_suffix(".0").unwrap_or(&rounded);
+    format!(
+        "{}{}",
+        rounded.replace('.', &separators().decimal.to_string()),
+        suffix
+    )
+}
+
+/// The characters a locale separates thousands and decimals with
+struct Separators {
+    thousands: char,
+    decimal: char,
+}
+
+/// The separators of the locale in `LC_ALL`, `LC_NUMERIC` or `LANG`, the first that is set
+fn separators() -> &'static Separators {
+    SEPARATORS.get_or_init(|| {
+        let locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|locale| !locale.is_empty())
+            .unwrap_or_default();
+        let language = locale.split(&['_', '.', '@', '-'][..]).next().unwrap_or("");
+        match language {
+            "de" | "nl" | "it" | "es" | "pt" | "id" | "da" | "tr" | "el" | "ro" | "sl" | "hr"
+            | "sr" | "vi" => Separators {
+                thousands: '.',
+                decimal: ',',
+            },
+            "fr" | "ru" | "sv" | "pl" | "cs" | "sk" | "fi" | "nb" | "nn" | "no" | "uk" | "hu"
+            | "bg" | "et" | "lv" | "lt" => Separators {
+                thousands: '\u{a0}',
+                decimal: ',',
+            },
+            _ => Separators {
+                thousands: ',',
+                decimal: '.',
+            },
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+
+    fn ago(seconds: i64) -> String {
+        let now = Local.ymd(2024, 6, 15).and_hms(12, 0, 0);
+        relative_time(&(now - Duration::seconds(seconds)), &now)
+    }
+
+    #[test]
+    fn relative_time_boundaries() {
+        assert_eq!(ago(0), "just now");
+        assert_eq!(ago(59), "just now");
+        assert_eq!(ago(60), "1 minute ago");
+        assert_eq!(ago(119), "1 minute ago");
+        assert_eq!(ago(120), "2 minutes ago");
+        assert_eq!(ago(3_599), "59 minutes ago");
+        assert_eq!(ago(3_600), "1 hour ago");
+        assert_eq!(ago(86_399), "23 hours ago");
+        assert_eq!(ago(86_400), "1 day ago");
+        assert_eq!(ago(2_591_999), "29 days ago");
+        assert_eq!(ago(2_592_000), "1 month ago");
+        assert_eq!(ago(31_535_999), "12 months ago");
+        assert_eq!(ago(31_536_000), "1 year ago");
+        assert_eq!(ago(3 * 31_536_000), "3 years ago");
+        assert_eq!(ago(-59), "just now");
+        assert_eq!(ago(-7_200), "in 2 hours");
+    }
+
+    #[test]
+    fn separated_counts() {
+        let sep = separators().thousands;
+        let separated = |digits: &str| digits.replace(',', &sep.to_string());
+        assert_eq!(separated_count(0), "0");
+        assert_eq!(separated_count(999), "999");
+        assert_eq!(separated_count(1_000), separated("1,000"));
+        assert_eq!(separated_count(999_999), separated("999,999"));
+        assert_eq!(separated_count(1_000_000), separated("1,000,000"));
+        assert_eq!(separated_count(184_729_384), separated("184,729,384"));
+    }
+
+    #[test]
+    fn humanized_counts() {
+        let dec = separators().decimal;
+        let humanized = |count: &str| count.replace('.', &dec.to_string());
+        assert_eq!(humanized_count(0), "0");
+        assert_eq!(humanized_count(999), "999");
+        assert_eq!(humanized_count(1_000), "1K");
+        assert_eq!(humanized_count(1_050), humanized("1.1K"));
+        assert_eq!(humanized_count(999_949), humanized("999.9K"));
+        assert_eq!(humanized_count(999_999), "1M");
+        assert_eq!(humanized_count(1_000_000), "1M");
+        assert_eq!(humanized_count(184_729_384), humanized("184.7M"));
+        assert_eq!(humanized_count(999_999_999), "1B");
+        assert_eq!(humanized_count(12_345_678_901), humanized("12.3B"));
+    }
+
+    #[test]
+    fn styles_parse() {
+        assert_eq!("Relative".parse(), Ok(DateStyle::Relative));
+        assert_eq!("humanized".parse(), Ok(CountStyle::Humanized));
+        assert!("fancy".parse::<CountStyle>().is_err());
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::crates_io::CratesSort;
+
+/// The maximum number of searches that are remembered
+const MAX_ENTRIES: usize = 200;
+
+/// A search that was submitted from the input popup
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub term: String,
+    pub sort: CratesSort,
+}
+
+/// A persistent, de-duplicated list of previous searches.  The most recent search is always first.
+pub struct SearchHistory {
+    entries: Vec<HistoryEntry>,
+    /// Where the history is saved.  None if there is no data directory available.
+    path: Option<PathBuf>,
+}
+
+impl SearchHistory {
+    /// Loads the history from the user's data directory.  A missing or unreadable history file
+    /// results in an empty history.
+    pub fn load() -> Self {
+        let path = dirs::data_dir().map(|dir| dir.join("cratuity").join("history.json"));
+        let entries = path
+            .as_deref()
+            .and_then(|path| Self::read(path).ok())
+            .unwrap_or_default();
+
+        Self { entries, path }
+    }
+
+    /// A history that starts empty and is never saved
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Self {
+            entries: Vec::new(),
+            path: None,
+        }
+    }
+
+    fn read(path: &Path) -> Result<Vec<HistoryEntry>, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, serde_json::to_string(&self.entries)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Records a search, moving it to the front if the term was already searched for.
+    pub fn push(&mut self, term: &str, sort: &CratesSort) -> Result<(), Box<dyn Error>> {
+        let term = term.trim();
+        if term.is_empty() {
+            return Ok(());
+        }
+
+        self.entries.retain(|entry| entry.term != term);
+        self.entries.insert(
+            0,
+            HistoryEntry {
+                term: term.to_string(),
+                sort: sort.clone(),
+            },
+        );
+        self.entries.truncate(MAX_ENTRIES);
+        self.save()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&HistoryEntry> {
+        self.entries.get(index)
+    }
+
+    /// Returns the entries fuzzy matching the pattern, best matches first.  Entries with the same
+    /// score keep their most-recent-first order.
+    pub fn fuzzy_filter(&self, pattern: &str) -> Vec<&HistoryEntry> {
+        let mut matches = self
+            .entries
+            .iter()
+            .filter_map(|entry| fuzzy_score(pattern, &entry.term).map(|score| (score, entry)))
+            .collect::<Vec<_>>();
+        // sort_by is stable, so recency is kept for equal scores
+        matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+        matches.into_iter().map(|(_, entry)| entry).collect()
+    }
+}
+
+/// Scores how well the pattern matches the candidate.  Every character of the pattern must appear
+/// in the candidate in order (ignoring case).  Consecutive matches and matches at the start of a
+/// word score higher.
+pub fn fuzzy_score(pattern: &str, candidate: &str) -> Option<i64> {
+    let mut score = 0;
+    let mut last_match: Option<usize> = None;
+    let candidate = candidate.to_lowercase().chars().collect::<Vec<_>>();
+    let mut pos = 0;
+
+    for p in pattern
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+    {
+        let found = pos + candidate[pos..].iter().position(|c| *c == p)?;
+
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || matches!(candidate[found - 1], '-' | '_' | ' ' | ':') {
+            score += 3;
+        }
+        score -= (found - pos) as i64 / 4;
+
+        last_match = Some(found);
+        pos = found + 1;
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(entries: &[&HistoryEntry]) -> Vec<String> {
+        entries.iter().map(|entry| entry.term.clone()).collect()
+    }
+
+    #[test]
+    fn push_dedupes_and_moves_to_front() {
+        let mut history = SearchHistory::in_memory();
+        for term in ["serde", "tokio", " serde ", "", "  "] {
+            history.push(term, &CratesSort::Relevance).unwrap();
+        }
+        history.push("tokio", &CratesSort::NewlyAdded).unwrap();
+        assert_eq!(terms(&history.fuzzy_filter("")), ["tokio", "serde"]);
+        assert_eq!(history.get(0).unwrap().sort, CratesSort::NewlyAdded);
+    }
+
+    #[test]
+    fn push_truncates() {
+        let mut history = SearchHistory::in_memory();
+        for i in 0..MAX_ENTRIES + 10 {
+            history
+                .push(&format!("search {}", i), &CratesSort::Relevance)
+                .unwrap();
+        }
+        assert_eq!(history.entries.len(), MAX_ENTRIES);
+        let newest = format!("search {}", MAX_ENTRIES + 9);
+        assert_eq!(history.get(0).unwrap().term, newest);
+        assert_eq!(history.get(MAX_ENTRIES - 1).unwrap().term, "search 10");
+        assert!(history.get(MAX_ENTRIES).is_none());
+    }
+
+    #[test]
+    fn fuzzy_scores() {
+        assert_eq!(fuzzy_score("xyz", "serde"), None);
+        assert_eq!(fuzzy_score("eds", "serde"), None);
+        assert!(fuzzy_score("", "serde").is_some());
+        assert_eq!(fuzzy_score("SERDE", "serde"), fuzzy_score("serde", "Serde"));
+        // consecutive and word start matches score higher
+        assert!(fuzzy_score("ser", "serde") > fuzzy_score("ser", "s-e-r"));
+        assert!(fuzzy_score("j", "serde json") > fuzzy_score("j", "serdejson"));
+    }
+
+    #[test]
+    fn fuzzy_match_ordering() {
+        let mut history = SearchHistory::in_memory();
+        for term in ["serde", "tokio", "sqlx serde", "async-std", "sdl2"] {
+            history.push(term, &CratesSort::Relevance).unwrap();
+        }
+        // the closer match comes first, even though it's older
+        assert_eq!(
+            terms(&history.fuzzy_filter("serde")),
+            ["serde", "sqlx serde"]
+        );
+        // a consecutive match at the start beats scattered ones, and tokio doesn't match at all
+        assert_eq!(
+            terms(&history.fuzzy_filter("sd")),
+            ["sdl2", "serde", "sqlx serde", "async-std"]
+        );
+    }
+}
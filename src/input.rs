@@ -1,14 +1,18 @@
 use std::{sync::mpsc::Sender, time::Duration};
 
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
 
 pub enum InputEvent {
     Char(char),
+    /// A character typed while holding the control key
+    Ctrl(char),
     Esc,
     Enter,
     Backspace,
     Right,
     Left,
+    AltRight,
+    AltLeft,
     Resize,
     Up,
     Down,
@@ -16,6 +20,8 @@ pub enum InputEvent {
     PageDown,
     Home,
     End,
+    Tab,
+    BackTab,
 }
 
 pub struct InputMonitor {
@@ -27,32 +33,50 @@ impl InputMonitor {
         InputMonitor { tx }
     }
 
+    /// Sends the key presses and resizes to the app until the terminal can't be read or the app
+    /// has stopped listening
     pub fn monitor(&self) {
         loop {
-            if let Ok(true) = event::poll(Duration::from_secs(10)) {
-                match event::read().unwrap() {
-                    Event::Key(key) => match key.code {
-                        KeyCode::Esc => self.tx.send(InputEvent::Esc).unwrap(),
-                        KeyCode::Enter => self.tx.send(InputEvent::Enter).unwrap(),
-                        KeyCode::Backspace => self.tx.send(InputEvent::Backspace).unwrap(),
-                        KeyCode::Right => self.tx.send(InputEvent::Right).unwrap(),
-                        KeyCode::Left => self.tx.send(InputEvent::Left).unwrap(),
-                        KeyCode::Up => self.tx.send(InputEvent::Up).unwrap(),
-                        KeyCode::Down => self.tx.send(InputEvent::Down).unwrap(),
-                        KeyCode::PageUp => self.tx.send(InputEvent::PageUp).unwrap(),
-                        KeyCode::PageDown => self.tx.send(InputEvent::PageDown).unwrap(),
-                        KeyCode::Home | KeyCode::Char('g') => {
-                            self.tx.send(InputEvent::Home).unwrap()
-                        }
-                        KeyCode::End | KeyCode::Char('G') => self.tx.send(InputEvent::End).unwrap(),
-                        KeyCode::Char(c) => self.tx.send(InputEvent::Char(c)).unwrap(),
-                        _ => {}
-                    },
-                    Event::Mouse(_) => {}
-                    Event::Resize(_, _) => {
-                        self.tx.send(InputEvent::Resize).unwrap();
+            let event = match event::poll(Duration::from_secs(10)) {
+                Ok(true) => match event::read() {
+                    Ok(event) => event,
+                    Err(_) => return,
+                },
+                Ok(false) => continue,
+                Err(_) => return,
+            };
+            let inpt = match event {
+                Event::Key(key) => match key.code {
+                    KeyCode::Esc => InputEvent::Esc,
+                    KeyCode::Enter => InputEvent::Enter,
+                    KeyCode::Backspace => InputEvent::Backspace,
+                    KeyCode::Right if key.modifiers.contains(KeyModifiers::ALT) => {
+                        InputEvent::AltRight
                     }
-                }
+                    KeyCode::Left if key.modifiers.contains(KeyModifiers::ALT) => {
+                        InputEvent::AltLeft
+                    }
+                    KeyCode::Right => InputEvent::Right,
+                    KeyCode::Left => InputEvent::Left,
+                    KeyCode::Up => InputEvent::Up,
+                    KeyCode::Down => InputEvent::Down,
+                    KeyCode::PageUp => InputEvent::PageUp,
+                    KeyCode::PageDown => InputEvent::PageDown,
+                    KeyCode::Tab => InputEvent::Tab,
+                    KeyCode::BackTab => InputEvent::BackTab,
+                    KeyCode::Home => InputEvent::Home,
+                    KeyCode::End => InputEvent::End,
+                    KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        InputEvent::Ctrl(c)
+                    }
+                    KeyCode::Char(c) => InputEvent::Char(c),
+                    _ => continue,
+                },
+                Event::Mouse(_) => continue,
+                Event::Resize(_, _) => InputEvent::Resize,
+            };
+            if self.tx.send(inpt).is_err() {
+                return;
             }
         }
     }
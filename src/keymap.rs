@@ -0,0 +1,262 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// The actions of the keys pressed while browsing, which the keymap can rebind
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+    Search,
+    Quit,
+    NextPage,
+    PrevPage,
+    Next,
+    Prev,
+    First,
+    Last,
+    Sort,
+    Filter,
+    Browse,
+    Dependencies,
+    Owners,
+    Versions,
+    Readme,
+    Outdated,
+    Mark,
+    Compare,
+    NewTab,
+    CloseTab,
+    Open,
+    Copy,
+    Add,
+}
+
+impl Action {
+    pub const ALL: [Action; 23] = [
+        Action::Search,
+        Action::Quit,
+        Action::NextPage,
+        Action::PrevPage,
+        Action::Next,
+        Action::Prev,
+        Action::First,
+        Action::Last,
+        Action::Sort,
+        Action::Filter,
+        Action::Browse,
+        Action::Dependencies,
+        Action::Owners,
+        Action::Versions,
+        Action::Readme,
+        Action::Outdated,
+        Action::Mark,
+        Action::Compare,
+        Action::NewTab,
+        Action::CloseTab,
+        Action::Open,
+        Action::Copy,
+        Action::Add,
+    ];
+
+    /// The name of the action in the config, e.g. `next-page`
+    pub fn name(self) -> String {
+        let mut name = String::new();
+        for c in format!("{:?}", self).chars() {
+            if c.is_uppercase() && !name.is_empty() {
+                name.push('-');
+            }
+            name.push(c.to_ascii_lowercase());
+        }
+        name
+    }
+
+    /// The keys the action is bound to without a keymap
+    pub fn default_keys(self) -> &'static [char] {
+        match self {
+            Action::Search => &['f', 'F'],
+            Action::Quit => &['q', 'Q'],
+            Action::NextPage => &['n', 'N'],
+            Action::PrevPage => &['p', 'P'],
+            Action::Next => &['j', 'J'],
+            Action::Prev => &['k', 'K'],
+            Action::First => &['g'],
+            Action::Last => &['G'],
+            Action::Sort => &['s', 'S'],
+            Action::Filter => &['e', 'E'],
+            Action::Browse => &['b', 'B'],
+            Action::Dependencies => &['d', 'D'],
+            Action::Owners => &['w', 'W'],
+            Action::Versions => &['l', 'L'],
+            Action::Readme => &['r', 'R'],
+            Action::Outdated => &['u', 'U'],
+            Action::Mark => &['m', 'M'],
+            Action::Compare => &['v', 'V'],
+            Action::NewTab => &['t', 'T'],
+            Action::CloseTab => &['x', 'X'],
+            Action::Open => &['o', 'O'],
+            Action::Copy => &['c', 'C'],
+            Action::Add => &['a', 'A'],
+        }
+    }
+}
+
+/// The keys the user rebound, by action.  An action that is rebound no longer answers to its
+/// default keys.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(try_from = "BTreeMap<String, char>", into = "BTreeMap<String, char>")]
+pub struct Keymap(BTreeMap<Action, char>);
+
+impl TryFrom<BTreeMap<String, char>> for Keymap {
+    type Error = String;
+
+    fn try_from(keys: BTreeMap<String, char>) -> Result<Self, Self::Error> {
+        keys.into_iter()
+            .map(|(name, key)| {
+                let action = Action::ALL
+                    .into_iter()
+                    .find(|action| action.name() == name)
+                    .ok_or_else(|| {
+                        let names = Action::ALL.map(Action::name);
+                        format!(
+                            "Unknown action {}, expected one of {}",
+                            name,
+                            names.join(", ")
+                        )
+                    })?;
+                Ok((action, key))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl From<Keymap> for BTreeMap<String, char> {
+    fn from(keymap: Keymap) -> Self {
+        keymap
+            .0
+            .into_iter()
+            .map(|(action, key)| (action.name(), key))
+            .collect()
+    }
+}
+
+impl Keymap {
+    /// Checks that no key is bound to two actions, whether both were rebound or the key is a
+    /// default key of an action that keeps its default keys
+    pub fn validate(&self) -> Result<(), String> {
+        for (action, key) in &self.0 {
+            let other = self
+                .action(*key)
+                .filter(|other| other != action)
+                .or_else(|| {
+                    Action::ALL.into_iter().find(|other| {
+                        other != action
+                            && !self.0.contains_key(other)
+                            && other.default_keys().contains(key)
+                    })
+                });
+            if let Some(other) = other {
+                return Err(format!(
+                    "The key '{}' is bound to both {} and {}",
+                    key,
+                    other.name(),
+                    action.name()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The key the action is bound to, the first of its default keys unless it was rebound
+    pub fn key_for(&self, action: Action) -> char {
+        self.0
+            .get(&action)
+            .copied()
+            .unwrap_or(action.default_keys()[0])
+    }
+
+    /// The action rebound to the key
+    fn action(&self, key: char) -> Option<Action> {
+        self.0
+            .iter()
+            .find(|(_, bound)| **bound == key)
+            .map(|(action, _)| *action)
+    }
+
+    /// Translates a key that was pressed into the default key of its action, so the rest of the
+    /// app only handles the default keys.  Default keys of rebound actions translate to nothing.
+    pub fn translate(&self, key: char) -> Option<char> {
+        if let Some(action) = self.action(key) {
+            return Some(action.default_keys()[0]);
+        }
+        let rebound = Action::ALL
+            .iter()
+            .any(|action| action.default_keys().contains(&key) && self.0.contains_key(action));
+        if rebound {
+            None
+        } else {
+            Some(key)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keymap(keys: &[(&str, char)]) -> Result<Keymap, String> {
+        let keys = keys
+            .iter()
+            .map(|(name, key)| (name.to_string(), *key))
+            .collect::<BTreeMap<_, _>>();
+        Keymap::try_from(keys)
+    }
+
+    #[test]
+    fn action_names() {
+        assert_eq!(Action::NextPage.name(), "next-page");
+        assert_eq!(Action::Quit.name(), "quit");
+        assert!(keymap(&[("close-tab", 'z')]).is_ok());
+    }
+
+    #[test]
+    fn unknown_action() {
+        let err = keymap(&[("jump", 'z')]).unwrap_err();
+        assert!(err.starts_with("Unknown action jump"), "{}", err);
+    }
+
+    #[test]
+    fn translate_rebound_key() {
+        let keymap = keymap(&[("next-page", 'l'), ("versions", 'n')]).unwrap();
+        assert_eq!(keymap.validate(), Ok(()));
+        assert_eq!(keymap.translate('l'), Some('n'));
+        assert_eq!(keymap.translate('n'), Some('l'));
+        assert_eq!(keymap.translate('q'), Some('q'));
+    }
+
+    #[test]
+    fn rebound_default_key_translates_to_none() {
+        let keymap = keymap(&[("quit", 'z')]).unwrap();
+        assert_eq!(keymap.translate('q'), None);
+        assert_eq!(keymap.translate('Q'), None);
+        assert_eq!(keymap.translate('z'), Some('q'));
+    }
+
+    #[test]
+    fn conflicting_keys() {
+        let err = keymap(&[("next", 'z'), ("prev", 'z')])
+            .unwrap()
+            .validate()
+            .unwrap_err();
+        assert_eq!(err, "The key 'z' is bound to both next and prev");
+        // `n` still belongs to next-page, which wasn't rebound
+        let err = keymap(&[("next", 'n')]).unwrap().validate().unwrap_err();
+        assert_eq!(err, "The key 'n' is bound to both next-page and next");
+    }
+
+    #[test]
+    fn key_for() {
+        let keymap = keymap(&[("new-tab", 'y')]).unwrap();
+        assert_eq!(keymap.key_for(Action::NewTab), 'y');
+        assert_eq!(keymap.key_for(Action::CloseTab), 'x');
+    }
+}
@@ -0,0 +1,187 @@
+/// Whether an SPDX license expression, such as `MIT OR Apache-2.0`, can be satisfied using only
+/// the allowed licenses.  Either side of an `OR` may be chosen, while both sides of an `AND`
+/// apply.  A license with an exception, such as `Apache-2.0 WITH LLVM-exception`, is allowed when
+/// either the license alone or the whole `WITH` expression is in the list.  Expressions that can't
+/// be parsed are never allowed.
+pub fn is_allowed(expr: &str, allowed: &[String]) -> bool {
+    let tokens = tokenize(expr);
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+        allowed,
+    };
+    match parser.or() {
+        Some(allowed) => parser.pos == tokens.len() && allowed,
+        None => false,
+    }
+}
+
+/// Splits the expression into license IDs, operators and parentheses.  The `/` of older crates
+/// is read as `OR`.
+fn tokenize(expr: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    for c in expr.chars() {
+        match c {
+            '(' | ')' | '/' => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+                tokens.push(if c == '/' {
+                    "OR".to_string()
+                } else {
+                    c.to_string()
+                });
+            }
+            c if c.is_whitespace() => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            }
+            c => token.push(c),
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+/// Whether the token is a license or exception ID rather than an operator or parenthesis
+fn is_id(token: &str) -> bool {
+    token != "(" && token != ")" && !["OR", "AND", "WITH"].contains(&token.to_uppercase().as_str())
+}
+
+struct Parser<'a> {
+    tokens: &'a [String],
+    pos: usize,
+    allowed: &'a [String],
+}
+
+impl Parser<'_> {
+    fn peek_op(&self, op: &str) -> bool {
+        self.tokens
+            .get(self.pos)
+            .is_some_and(|token| token.eq_ignore_ascii_case(op))
+    }
+
+    fn is_listed(&self, license: &str) -> bool {
+        self.allowed
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(license))
+    }
+
+    fn or(&mut self) -> Option<bool> {
+        let mut allowed = self.and()?;
+        while self.peek_op("OR") {
+            self.pos += 1;
+            allowed |= self.and()?;
+        }
+        Some(allowed)
+    }
+
+    fn and(&mut self) -> Option<bool> {
+        let mut allowed = self.license()?;
+        while self.peek_op("AND") {
+            self.pos += 1;
+            allowed &= self.license()?;
+        }
+        Some(allowed)
+    }
+
+    fn license(&mut self) -> Option<bool> {
+        let token = self.tokens.get(self.pos)?;
+        self.pos += 1;
+        if token == "(" {
+            let allowed = self.or()?;
+            if self.tokens.get(self.pos)? != ")" {
+                return None;
+            }
+            self.pos += 1;
+            return Some(allowed);
+        }
+        if !is_id(token) {
+            return None;
+        }
+
+        let mut allowed = self.is_listed(token);
+        if self.peek_op("WITH") {
+            let exception = self.tokens.get(self.pos + 1).filter(|token| is_id(token))?;
+            self.pos += 2;
+            allowed |= self.is_listed(&format!("{} WITH {}", token, exception));
+        }
+        Some(allowed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowed(expr: &str, licenses: &[&str]) -> bool {
+        let licenses = licenses.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        is_allowed(expr, &licenses)
+    }
+
+    #[test]
+    fn or_and() {
+        assert!(allowed("MIT OR Apache-2.0", &["Apache-2.0"]));
+        assert!(!allowed("MIT AND Apache-2.0", &["Apache-2.0"]));
+        assert!(allowed("MIT AND Apache-2.0", &["MIT", "Apache-2.0"]));
+        assert!(allowed("MIT/Apache-2.0", &["MIT"]));
+        assert!(allowed("mit or apache-2.0", &["Apache-2.0"]));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let expr = "MIT OR Apache-2.0 AND GPL-3.0";
+        assert!(allowed(expr, &["MIT"]));
+        assert!(!allowed(expr, &["Apache-2.0"]));
+        assert!(allowed(expr, &["Apache-2.0", "GPL-3.0"]));
+    }
+
+    #[test]
+    fn parentheses() {
+        let expr = "(MIT OR Apache-2.0) AND GPL-3.0";
+        assert!(!allowed(expr, &["MIT"]));
+        assert!(allowed(expr, &["MIT", "GPL-3.0"]));
+        assert!(allowed("((MIT))", &["MIT"]));
+        assert!(allowed(
+            "Unicode-DFS-2016 AND (MIT OR Apache-2.0)",
+            &["Unicode-DFS-2016", "MIT"]
+        ));
+    }
+
+    #[test]
+    fn with_exception() {
+        let expr = "Apache-2.0 WITH LLVM-exception";
+        assert!(allowed(expr, &["Apache-2.0"]));
+        assert!(allowed(expr, &["Apache-2.0 WITH LLVM-exception"]));
+        assert!(!allowed(expr, &["MIT"]));
+        // WITH binds tighter than AND and OR
+        assert!(allowed(
+            "MIT AND Apache-2.0 WITH LLVM-exception",
+            &["MIT", "Apache-2.0"]
+        ));
+        assert!(allowed(
+            "GPL-2.0 WITH Classpath-exception-2.0 OR MIT",
+            &["MIT"]
+        ));
+    }
+
+    #[test]
+    fn invalid_expressions() {
+        for expr in [
+            "",
+            "MIT OR",
+            "(MIT",
+            "MIT)",
+            "OR MIT",
+            "MIT WITH",
+            "MIT WITH OR",
+            "MIT MIT",
+        ] {
+            assert!(!allowed(expr, &["MIT"]), "{}", expr);
+        }
+    }
+}
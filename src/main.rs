@@ -1,42 +1,57 @@
 use std::{
+    env,
     error::Error,
     io,
-    io::Write,
+    path::PathBuf,
     sync::mpsc::{self},
     thread,
 };
 
-use app::App;
+use app::{App, StartState};
+use config::{ClipboardBackend, Config};
+use crates_io::CrateSearcher;
+use format::{CountStyle, DateStyle};
 
-use crates_io::{CrateSearch, CrateSearchResponse, CrateSearcher, CratesSort};
-use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
 use input::InputMonitor;
 
-use comfy_table::{ContentArrangement, Row, Table, ToRow};
-use structopt::StructOpt;
-
-use tui::{backend::CrosstermBackend, Terminal};
-use widgets::STR_FORMAT;
+use output::OutputFormat;
+use structopt::{clap::Shell, StructOpt};
+use terminal::TerminalGuard;
 
+mod advisory;
 mod app;
+mod cli;
+mod config;
 mod crates_io;
+mod filter;
+mod format;
+mod history;
 mod input;
+mod keymap;
+mod license;
+mod manifest;
+mod nav;
+mod osc52;
+mod outdated;
+mod output;
+mod project;
+mod readme;
+mod terminal;
+mod theme;
 mod toast;
 mod widgets;
 
-const TABLE_STYLE: &'static str = "││ ─├─┼┤│─┼├┤ ┴  └┘";
+/// The most crates shown on a page of the TUI.  It fetches ten pages at a time, which can't be more
+/// than the 100 results crates.io gives at once.
+const MAX_ITEMS_PER_PAGE: u32 = 10;
+
+const TABLE_STYLE: &str = "││ ─├─┼┤│─┼├┤ ┴  └┘";
 
 pub(crate) fn ceil_div(a: u32, b: u32) -> u32 {
     if b == 0 {
         panic!("attempt to divide by zero");
-    } else if a == 0 {
-        0
     } else {
-        (a + b - 1) / b
+        a.div_ceil(b)
     }
 }
 
@@ -44,47 +59,268 @@ pub(crate) fn ceil_div(a: u32, b: u32) -> u32 {
 #[structopt(name = "Cratuity", about = "A simple TUI for searching Crates.io")]
 /// A TUI for searching crates.io in the terminal.  
 ///
-/// Alternatively, the find option may be used to bypass the TUI and output the
-/// results directly to the terminal.
+/// Alternatively, the subcommands bypass the TUI and output the results directly
+/// to the terminal.
 pub struct AppArgs {
+    /// The same as the search subcommand, unless --interactive is given
     #[structopt(short, long)]
     pub find: Option<String>,
 
-    #[structopt(short, long, default_value)]
-    pub sort: CratesSort,
+    /// Opens the TUI with the search from --find already run, using --sort, --page and
+    /// --count as the page size
+    #[structopt(short, long)]
+    pub interactive: bool,
+
+    /// A search to open the TUI with, the same as --find with --interactive
+    pub query: Vec<String>,
+
+    #[structopt(flatten)]
+    pub search: cli::SearchArgs,
+
+    /// How the output of the subcommands is printed
+    #[structopt(
+        long,
+        global = true,
+        default_value = "table",
+        possible_values = &OutputFormat::NAMES,
+        case_insensitive = true
+    )]
+    pub format: OutputFormat,
+
+    /// The comma separated fields to print, in order, e.g. name,downloads,description
+    #[structopt(long, global = true, use_delimiter = true)]
+    pub fields: Vec<String>,
+
+    /// A local clone of the RustSec advisory database, used to flag vulnerable crates
+    #[structopt(long, parse(from_os_str))]
+    pub advisory_db: Option<PathBuf>,
+
+    /// The registry to search, crates-io or one from the config
+    #[structopt(long, global = true)]
+    pub registry: Option<String>,
+
+    /// How dates are shown: format, with --date-format, iso or relative
+    #[structopt(
+        long,
+        global = true,
+        possible_values = &DateStyle::NAMES,
+        case_insensitive = true
+    )]
+    pub date_style: Option<DateStyle>,
+
+    /// How dates are shown, as a chrono format string, e.g. %Y-%m-%d
+    #[structopt(long, global = true)]
+    pub date_format: Option<String>,
+
+    /// How counts such as downloads are shown: plain, separated or humanized
+    #[structopt(
+        long,
+        global = true,
+        possible_values = &CountStyle::NAMES,
+        case_insensitive = true
+    )]
+    pub count_style: Option<CountStyle>,
+
+    /// The command used to open links
+    #[structopt(long)]
+    pub browser: Option<String>,
+
+    /// How text is copied to the clipboard
+    #[structopt(long, possible_values = &["auto", "system", "osc52"], case_insensitive = true)]
+    pub clipboard: Option<ClipboardBackend>,
+
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Command {
+    /// Searches crates.io, with the same query syntax as the TUI
+    Search {
+        #[structopt(required = true)]
+        query: Vec<String>,
+    },
+    /// Shows the details of a crate
+    Info {
+        #[structopt(name = "crate")]
+        name: String,
+    },
+    /// Lists the versions of a crate, the newest first
+    Versions {
+        #[structopt(name = "crate")]
+        name: String,
+    },
+    /// Lists the dependencies of a version of a crate, or of its newest version
+    Deps {
+        #[structopt(name = "crate[@version]")]
+        spec: String,
+    },
+    /// Lists the crates that depend on a crate, the most downloaded first.  --count sets how
+    /// many, 10 by default.
+    Rdeps {
+        #[structopt(name = "crate")]
+        name: String,
+    },
+    /// Lists the owners of a crate
+    Owners {
+        #[structopt(name = "crate")]
+        name: String,
+    },
+    /// Lists how up to date the dependencies of the Cargo project in the working directory are
+    Outdated,
+    /// Shows, locates or creates the config file
+    Config(ConfigCommand),
+    /// Prints a completion script for a shell, e.g. `cratuity completions bash > cratuity.bash`
+    Completions {
+        #[structopt(possible_values = &["bash", "zsh", "fish"])]
+        shell: Shell,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+pub enum ConfigCommand {
+    /// Prints the settings in effect, after the command line overrides the config file
+    Show,
+    /// Prints the location of the config file
+    Path,
+    /// Writes a config file with every setting commented out at its default
+    Init,
+}
+
+impl AppArgs {
+    /// Overrides the config with the settings given on the command line
+    fn override_config(&self, config: &mut Config) {
+        if let Some(sort) = &self.search.sort {
+            config.sort = sort.clone();
+        }
+        if let Some(registry) = &self.registry {
+            config.registry = registry.clone();
+        }
+        if let Some(date_style) = self.date_style {
+            config.date_style = date_style;
+        }
+        if let Some(date_format) = &self.date_format {
+            config.date_format = date_format.clone();
+        }
+        if let Some(count_style) = self.count_style {
+            config.count_style = count_style;
+        }
+        if let Some(browser) = &self.browser {
+            config.browser = Some(browser.clone());
+        }
+        if let Some(clipboard) = self.clipboard {
+            config.clipboard = clipboard;
+        }
+        if let Some(advisory_db) = &self.advisory_db {
+            config.advisory_db = Some(advisory_db.clone());
+        }
+    }
+}
 
-    #[structopt(short, long, default_value = "5")]
-    pub count: usize,
+fn main() {
+    terminal::install_panic_hook();
+    // errors are printed for people and scripts to read, rather than debug formatted
+    if let Err(err) = run() {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let matches = AppArgs::clap().get_matches();
+fn run() -> Result<(), Box<dyn Error>> {
+    let matches = AppArgs::clap().get_matches_from(cli::normalize_sort_args(env::args_os()));
     if matches.is_present("help") {
         println!("{}", matches.usage());
         return Ok(());
     }
 
     let args: AppArgs = AppArgs::from_clap(&matches);
-    if let Some(find) = args.find {
-        cli_search(find.as_str(), args.sort, args.count)?;
+    // the config file can be found and created even when it's invalid
+    match &args.command {
+        Some(Command::Config(ConfigCommand::Path)) => {
+            let path = Config::path().ok_or("No config directory was found")?;
+            println!("{}", path.display());
+            return Ok(());
+        }
+        Some(Command::Config(ConfigCommand::Init)) => {
+            let path = Config::path().ok_or("No config directory was found")?;
+            Config::init(&path)?;
+            println!("Wrote {}", path.display());
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let mut config = Config::load()?;
+    args.override_config(&mut config);
+    config.validate()?;
+    config.init_display();
+
+    let output = cli::Output {
+        format: args.format,
+        fields: &args.fields,
+    };
+    if let Some(command) = &args.command {
+        let mut client = CrateSearcher::new(config.api()?, config.cache_ttl())?;
+        match command {
+            Command::Search { query } => {
+                cli::search(&client, &query.join(" "), &args.search, &config, &output)?
+            }
+            Command::Info { name } => cli::info(&client, name, &output)?,
+            Command::Versions { name } => cli::versions(&client, name, &output)?,
+            Command::Deps { spec } => cli::deps(&client, spec, &output)?,
+            Command::Rdeps { name } => cli::rdeps(&client, name, args.search.count, &output)?,
+            Command::Owners { name } => cli::owners(&client, name, &output)?,
+            Command::Outdated => cli::outdated(&mut client, &output)?,
+            Command::Config(_) => print!("{}", config.to_toml()?),
+            Command::Completions { shell } => AppArgs::clap().gen_completions_to(
+                env!("CARGO_PKG_NAME"),
+                *shell,
+                &mut io::stdout(),
+            ),
+        }
+
+        return Ok(());
+    }
+    if let (Some(find), false) = (&args.find, args.interactive) {
+        let client = CrateSearcher::new(config.api()?, config.cache_ttl())?;
+        cli::search(&client, find, &args.search, &config, &output)?;
 
         return Ok(());
     }
 
+    let search = if args.query.is_empty() {
+        args.find.clone()
+    } else {
+        Some(args.query.join(" "))
+    };
+    let items_per_page = args.search.count.unwrap_or(config.page_size);
+    if !(1..=MAX_ITEMS_PER_PAGE).contains(&items_per_page) {
+        return Err(format!(
+            "--count must be between 1 and {} in the TUI",
+            MAX_ITEMS_PER_PAGE
+        )
+        .into());
+    }
+    if args.search.all || args.search.limit.is_some() {
+        return Err("--all and --limit only apply to printed results, such as with --find".into());
+    }
+    let start = StartState {
+        search,
+        sort: config.sort.clone(),
+        page: args.search.page.max(1),
+        items_per_page,
+    };
+
     let (tx, rx) = mpsc::channel();
     thread::spawn(move || InputMonitor::new(tx).monitor());
-    let mut app = App::new(rx);
-
-    let mut stdout = io::stdout();
-    enable_raw_mode()?;
+    let mut app = App::new(rx, config, start);
 
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-    terminal.clear()?;
+    // the terminal is restored when the guard is dropped, even on an error
+    let mut guard = TerminalGuard::new()?;
+    guard.terminal.clear()?;
 
     loop {
-        terminal.draw(|f| {
+        guard.terminal.draw(|f| {
             app.draw(f);
         })?;
 
@@ -93,55 +329,36 @@ fn main() -> Result<(), Box<dyn Error>> {
             break;
         }
     }
-    disable_raw_mode()?;
-    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
-    // terminal.clear().unwrap();
     Ok(())
 }
 
-fn cli_search(term: &str, sort: CratesSort, count: usize) -> Result<(), Box<dyn Error>> {
-    let crate_search = CrateSearcher::new()?;
-    let resp = crate_search.search_sorted_count(term, 1, count as u32, &sort)?;
-    print_crates_table(resp)
-}
-
-fn print_crates_table(crates: CrateSearchResponse) -> Result<(), Box<dyn Error>> {
-    let mut table = Table::new();
-    table.set_content_arrangement(ContentArrangement::Dynamic);
-    table.load_preset(TABLE_STYLE);
-
-    table.set_header(vec![
-        "Name",
-        "Created",
-        "Updated",
-        "Downloads",
-        "Recent Downloads",
-        "Max Version",
-        "Newest Version",
-        "Description",
-    ]);
-
-    for crte in crates.crates {
-        table.add_row(crte);
-    }
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
 
-    println!("{}", table);
+    use super::*;
 
-    Ok(())
-}
+    #[test]
+    fn bash_completions_offer_sorts() {
+        let mut script = Vec::new();
+        AppArgs::clap().gen_completions_to("cratuity", Shell::Bash, &mut script);
+        let script = String::from_utf8(script).unwrap();
+        let sorts = format!("compgen -W \"{}\"", crates_io::CratesSort::NAMES.join(" "));
+        assert!(script.contains(&sorts));
+    }
 
-impl ToRow for CrateSearch {
-    fn to_row(self) -> Row {
-        let desc = self.description.unwrap_or("".to_string());
-        Row::from(vec![
-            self.name,
-            self.created_at.format(STR_FORMAT).to_string(),
-            self.updated_at.format(STR_FORMAT).to_string(),
-            self.downloads.to_string(),
-            self.recent_downloads.to_string(),
-            self.max_version,
-            self.newest_version,
-            desc,
-        ])
+    #[test]
+    fn sort_spellings_parse() {
+        for sort in ["all time downloads", "Recent_Update", "NEWEST"] {
+            let args = ["cratuity", "search", "serde", "--sort", sort].map(OsString::from);
+            let matches = AppArgs::clap()
+                .get_matches_from_safe(cli::normalize_sort_args(args))
+                .unwrap();
+            assert!(
+                AppArgs::from_clap(&matches).search.sort.is_some(),
+                "{}",
+                sort
+            );
+        }
     }
 }
@@ -0,0 +1,569 @@
+use std::{
+    error::Error,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use toml_edit::{value, Array, DocumentMut, Item, Table, TableLike, Value};
+
+/// The number of unchanged lines shown around each change in a diff
+const DIFF_CONTEXT: usize = 3;
+
+/// The kinds of dependency tables in a manifest
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DepKind {
+    Normal,
+    Dev,
+    Build,
+}
+
+impl DepKind {
+    pub const ALL: [DepKind; 3] = [DepKind::Normal, DepKind::Dev, DepKind::Build];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            DepKind::Normal => "dependencies",
+            DepKind::Dev => "dev-dependencies",
+            DepKind::Build => "build-dependencies",
+        }
+    }
+}
+
+/// A table dependencies can be added to, optionally only for a target
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DepTable {
+    pub kind: DepKind,
+    pub target: Option<String>,
+}
+
+impl fmt::Display for DepTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.target {
+            Some(target) => write!(f, "[target.'{}'.{}]", target, self.kind.key()),
+            None => write!(f, "[{}]", self.kind.key()),
+        }
+    }
+}
+
+/// A dependency declared in a manifest
+#[derive(Clone, Debug)]
+pub struct ManifestDep {
+    /// The name of the crate, which may differ from the dependency's key if it was renamed
+    pub name: String,
+    /// The version requirement.  None for path and git dependencies without a version, and for
+    /// dependencies inherited from the workspace.
+    pub req: Option<String>,
+    /// Whether the dependency is inherited from `[workspace.dependencies]`
+    pub inherited: bool,
+    /// Whether the dependency comes from a path or git rather than a registry
+    pub local: bool,
+}
+
+/// A line of the difference between the manifest on disk and the edited one
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffLine {
+    Same(String),
+    Added(String),
+    Removed(String),
+    /// Unchanged lines that aren't shown
+    Skipped,
+}
+
+/// A `Cargo.toml` that is edited in memory, keeping its formatting and comments
+pub struct Manifest {
+    pub path: PathBuf,
+    original: String,
+    doc: DocumentMut,
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let original = fs::read_to_string(path)
+            .map_err(|err| format!("Cannot read {}: {}", path.display(), err))?;
+        let doc = original
+            .parse::<DocumentMut>()
+            .map_err(|err| format!("Invalid manifest {}: {}", path.display(), err))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            original,
+            doc,
+        })
+    }
+
+    /// The nearest `Cargo.toml` in the directory or any of its parents
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|dir| dir.join("Cargo.toml"))
+            .find(|path| path.is_file())
+    }
+
+    /// The manifests a dependency can be added to: the nearest package first, then the other
+    /// members of its workspace.
+    pub fn candidates(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        let nearest = Self::find(dir).ok_or("No Cargo.toml found")?;
+        let mut candidates = Vec::new();
+        if Self::load(&nearest)?.doc.contains_key("package") {
+            candidates.push(nearest.clone());
+        }
+
+        if let Some(root) = Self::workspace_root(&nearest) {
+            if root.doc.contains_key("package") {
+                candidates.push(root.path.clone());
+            }
+            candidates.extend(root.members());
+        }
+
+        let mut seen = Vec::new();
+        candidates.retain(|path| {
+            let path = path.canonicalize().unwrap_or_else(|_| path.clone());
+            let new = !seen.contains(&path);
+            seen.push(path);
+            new
+        });
+
+        if candidates.is_empty() {
+            Err(format!("{} has no packages", nearest.display()).into())
+        } else {
+            Ok(candidates)
+        }
+    }
+
+    /// The root manifest of the workspace the manifest belongs to, which may be the manifest itself
+    pub fn workspace_root(manifest: &Path) -> Option<Self> {
+        manifest
+            .parent()
+            .into_iter()
+            .flat_map(Path::ancestors)
+            .map(|dir| dir.join("Cargo.toml"))
+            .filter(|path| path.is_file())
+            .find_map(|path| {
+                Self::load(&path)
+                    .ok()
+                    .filter(|m| m.doc.contains_key("workspace"))
+            })
+    }
+
+    /// The manifests of the workspace members.  Only `*` in the last part of a member pattern is
+    /// supported, which covers the usual `crates/*`.
+    fn members(&self) -> Vec<PathBuf> {
+        let root = self.path.parent().unwrap_or_else(|| Path::new("."));
+        let patterns = |key: &str| -> Vec<String> {
+            self.doc
+                .get("workspace")
+                .and_then(|workspace| workspace.get(key))
+                .and_then(Item::as_array)
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let exclude = patterns("exclude")
+            .iter()
+            .map(|path| root.join(path))
+            .collect::<Vec<_>>();
+
+        let mut members = Vec::new();
+        for pattern in patterns("members") {
+            let pattern = Path::new(&pattern);
+            let name = pattern
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if name.contains('*') {
+                let parent = root.join(pattern.parent().unwrap_or_else(|| Path::new("")));
+                let mut dirs = fs::read_dir(&parent)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Result::ok)
+                    .map(|entry| entry.path())
+                    .filter(|path| {
+                        path.file_name()
+                            .is_some_and(|file| wildcard_match(&name, &file.to_string_lossy()))
+                    })
+                    .collect::<Vec<_>>();
+                dirs.sort();
+                members.extend(dirs);
+            } else {
+                members.push(root.join(pattern));
+            }
+        }
+
+        members
+            .into_iter()
+            .filter(|dir| !exclude.contains(dir))
+            .map(|dir| dir.join("Cargo.toml"))
+            .filter(|path| path.is_file())
+            .collect()
+    }
+
+    /// The tables a dependency can be added to: the three plain tables, then the tables of any
+    /// targets the manifest already has.
+    pub fn tables(&self) -> Vec<DepTable> {
+        let mut tables = DepKind::ALL
+            .iter()
+            .map(|kind| DepTable {
+                kind: *kind,
+                target: None,
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(targets) = self.doc.get("target").and_then(Item::as_table_like) {
+            for (target, _) in targets.iter() {
+                tables.extend(DepKind::ALL.iter().map(|kind| DepTable {
+                    kind: *kind,
+                    target: Some(target.to_string()),
+                }));
+            }
+        }
+
+        tables
+    }
+
+    /// The dependencies in all of the manifest's dependency tables
+    pub fn dependencies(&self) -> Vec<ManifestDep> {
+        let mut deps = Vec::new();
+        for table in self.tables() {
+            let mut item = self.doc.as_item();
+            if let Some(target) = &table.target {
+                item = &item["target"][target.as_str()];
+            }
+            if let Some(entries) = item.get(table.kind.key()).and_then(Item::as_table_like) {
+                deps.extend(Self::read_dependencies(entries));
+            }
+        }
+        deps
+    }
+
+    /// The dependencies shared by the workspace's members in `[workspace.dependencies]`
+    pub fn workspace_dependencies(&self) -> Vec<ManifestDep> {
+        self.doc
+            .get("workspace")
+            .and_then(|workspace| workspace.get("dependencies"))
+            .and_then(Item::as_table_like)
+            .map(Self::read_dependencies)
+            .unwrap_or_default()
+    }
+
+    fn read_dependencies(entries: &dyn TableLike) -> Vec<ManifestDep> {
+        entries
+            .iter()
+            .map(|(key, value)| {
+                let dep = value.as_table_like();
+                let field = |field: &str| dep.and_then(|dep| dep.get(field));
+                let str_field = |name: &str| field(name).and_then(Item::as_str);
+                ManifestDep {
+                    // renamed dependencies give the crate's real name in `package`
+                    name: str_field("package").unwrap_or(key).to_string(),
+                    req: value
+                        .as_str()
+                        .or_else(|| str_field("version"))
+                        .map(String::from),
+                    inherited: field("workspace").and_then(Item::as_bool).unwrap_or(false),
+                    local: field("path").is_some() || field("git").is_some(),
+                }
+            })
+            .collect()
+    }
+
+    /// Adds the dependency to the table.  `toml` is a line in the format of
+    /// [`crate::crates_io::CrateSearch::get_toml_str`].  A dependency that is already there as a
+    /// table keeps its other keys, such as `optional`, and only has its version updated and the
+    /// new features added.  If the table was sorted it is kept sorted.
+    pub fn add_dependency(&mut self, table: &DepTable, toml: &str) -> Result<(), Box<dyn Error>> {
+        let dep = toml.parse::<DocumentMut>()?;
+
+        let mut parent = self.doc.as_table_mut();
+        if let Some(target) = &table.target {
+            parent = implicit_table(parent, "target")?;
+            parent = implicit_table(parent, target)?;
+        }
+        let deps = parent
+            .entry(table.kind.key())
+            .or_insert_with(|| Item::Table(Table::new()))
+            .as_table_like_mut()
+            .ok_or_else(|| format!("{} is not a table", table))?;
+
+        let keys = deps
+            .iter()
+            .map(|(key, _)| key.to_string())
+            .collect::<Vec<_>>();
+        let sorted = keys.windows(2).all(|pair| pair[0] <= pair[1]);
+
+        for (key, value) in dep.iter() {
+            if let Some(existing) = deps.get_mut(key).and_then(Item::as_table_like_mut) {
+                update_dependency(existing, value);
+                continue;
+            }
+            let mut value = value.clone();
+            if let Some(value) = value.as_value_mut() {
+                value.decor_mut().clear();
+            }
+            deps.insert(key, value);
+        }
+        if sorted {
+            deps.sort_values();
+        }
+
+        Ok(())
+    }
+
+    /// The changes made to the manifest, with a few lines of context around each change.  Empty
+    /// when nothing changed.
+    pub fn diff(&self) -> Vec<DiffLine> {
+        let edited = self.doc.to_string();
+        let full = diff_lines(
+            &self.original.lines().collect::<Vec<_>>(),
+            &edited.lines().collect::<Vec<_>>(),
+        );
+
+        let changed = full
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        if changed.is_empty() {
+            return Vec::new();
+        }
+        let near_change = |i: usize| {
+            changed
+                .iter()
+                .any(|c| i + DIFF_CONTEXT >= *c && i <= c + DIFF_CONTEXT)
+        };
+
+        let mut lines = Vec::new();
+        for (i, line) in full.into_iter().enumerate() {
+            if near_change(i) {
+                lines.push(line);
+            } else if lines.last() != Some(&DiffLine::Skipped) {
+                lines.push(DiffLine::Skipped);
+            }
+        }
+        lines
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        fs::write(&self.path, self.doc.to_string())
+            .map_err(|err| format!("Cannot write {}: {}", self.path.display(), err))?;
+        Ok(())
+    }
+}
+
+/// Sets the version of a dependency that is written as a table and adds the features it doesn't
+/// have yet, leaving the rest of the table and its formatting as it is
+fn update_dependency(existing: &mut dyn TableLike, dep: &Item) {
+    let (version, features) = match dep.as_table_like() {
+        Some(dep) => (dep.get("version"), dep.get("features")),
+        None => (Some(dep), None),
+    };
+
+    if let Some(version) = version.and_then(Item::as_str) {
+        match existing.get_mut("version").and_then(Item::as_value_mut) {
+            Some(existing) => {
+                let decor = existing.decor().clone();
+                *existing = Value::from(version);
+                *existing.decor_mut() = decor;
+            }
+            None => {
+                existing.insert("version", value(version));
+            }
+        }
+    }
+
+    let features = features
+        .and_then(Item::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str);
+    for feature in features {
+        let existing = existing
+            .entry("features")
+            .or_insert_with(|| value(Array::new()))
+            .as_array_mut();
+        if let Some(existing) = existing {
+            if !existing.iter().any(|other| other.as_str() == Some(feature)) {
+                existing.push(feature);
+            }
+        }
+    }
+}
+
+/// Gets the sub-table, creating an implicit one so that no empty header is written
+fn implicit_table<'a>(parent: &'a mut Table, key: &str) -> Result<&'a mut Table, Box<dyn Error>> {
+    parent
+        .entry(key)
+        .or_insert_with(|| {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            Item::Table(table)
+        })
+        .as_table_mut()
+        .ok_or_else(|| format!("`{}` is not a table", key).into())
+}
+
+/// Whether the name matches a pattern with at most one `*`
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            name.len() >= prefix.len() + suffix.len()
+                && name.starts_with(prefix)
+                && name.ends_with(suffix)
+        }
+        None => pattern == name,
+    }
+}
+
+/// A line diff using the longest common subsequence of the lines
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<DiffLine> {
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            // removed lines come before the lines that replace them
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(toml: &str) -> Manifest {
+        Manifest {
+            path: PathBuf::from("Cargo.toml"),
+            original: toml.to_string(),
+            doc: toml.parse().unwrap(),
+        }
+    }
+
+    fn add(toml: &str, dep: &str) -> String {
+        let mut manifest = manifest(toml);
+        let table = DepTable {
+            kind: DepKind::Normal,
+            target: None,
+        };
+        manifest.add_dependency(&table, dep).unwrap();
+        manifest.doc.to_string()
+    }
+
+    #[test]
+    fn add_new_dependency_sorted() {
+        let toml = "[dependencies]\nanyhow = \"1\"\ntoml = \"0.5\"\n";
+        assert_eq!(
+            add(toml, "serde = \"1.0.200\""),
+            "[dependencies]\nanyhow = \"1\"\nserde = \"1.0.200\"\ntoml = \"0.5\"\n"
+        );
+    }
+
+    #[test]
+    fn replace_version_string() {
+        let toml = "[dependencies]\nserde = \"1.0.100\"\n";
+        assert_eq!(
+            add(toml, "serde = \"1.0.200\""),
+            "[dependencies]\nserde = \"1.0.200\"\n"
+        );
+    }
+
+    #[test]
+    fn keep_inline_table_keys() {
+        let toml = "[dependencies]\nserde = { version = \"1.0.100\", optional = true, \
+                    default-features = false, features = [\"std\"] }\n";
+        assert_eq!(
+            add(
+                toml,
+                "serde = { version = \"1.0.200\", features = [\"derive\", \"std\"] }"
+            ),
+            "[dependencies]\nserde = { version = \"1.0.200\", optional = true, \
+             default-features = false, features = [\"std\", \"derive\"] }\n"
+        );
+    }
+
+    #[test]
+    fn keep_table_keys() {
+        let toml = "[dependencies.serde]\nversion = \"1.0.100\" # pinned\noptional = true\n";
+        assert_eq!(
+            add(toml, "serde = \"1.0.200\""),
+            "[dependencies.serde]\nversion = \"1.0.200\" # pinned\noptional = true\n"
+        );
+    }
+
+    #[test]
+    fn diff_empty() {
+        assert_eq!(diff_lines(&[], &[]), []);
+        assert_eq!(diff_lines(&[], &["a"]), [DiffLine::Added("a".to_string())]);
+        assert_eq!(
+            diff_lines(&["a"], &[]),
+            [DiffLine::Removed("a".to_string())]
+        );
+    }
+
+    #[test]
+    fn diff_identical() {
+        let lines = ["a", "b", "c"];
+        assert_eq!(
+            diff_lines(&lines, &lines),
+            lines.map(|line| DiffLine::Same(line.to_string()))
+        );
+        assert_eq!(manifest("[package]\nname = \"a\"\n").diff(), []);
+    }
+
+    #[test]
+    fn diff_changes() {
+        let same = |line: &str| DiffLine::Same(line.to_string());
+        assert_eq!(
+            diff_lines(&["a", "b", "c"], &["a", "x", "c", "d"]),
+            [
+                same("a"),
+                DiffLine::Removed("b".to_string()),
+                DiffLine::Added("x".to_string()),
+                same("c"),
+                DiffLine::Added("d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_skips_distant_lines() {
+        let mut manifest = manifest(
+            "[package]\nname = \"a\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\
+             description = \"d\"\n\n[dependencies]\nanyhow = \"1\"\n",
+        );
+        let table = DepTable {
+            kind: DepKind::Normal,
+            target: None,
+        };
+        manifest.add_dependency(&table, "serde = \"1\"").unwrap();
+        let diff = manifest.diff();
+        assert_eq!(diff[0], DiffLine::Skipped);
+        assert_eq!(
+            diff.last(),
+            Some(&DiffLine::Added("serde = \"1\"".to_string()))
+        );
+        assert_eq!(diff.len(), 5);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    crates_io::{CratesSort, SearchQuery},
+    filter::SearchFilter,
+};
+
+/// The screens that can be shown in the main area of the app
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum View {
+    /// The paged search results
+    Search,
+    /// The details of the named crate
+    Details(String),
+    /// The dependencies of the named crate at the given version
+    Dependencies(String, String),
+    /// The owners of the named crate
+    Owners(String),
+    /// A side-by-side comparison of the named crates
+    Compare(Vec<String>),
+    /// The README of the named crate at the given version
+    Readme(String, String),
+    /// All versions of the named crate
+    Versions(String),
+    /// How up to date the current project's dependencies are
+    Outdated,
+    /// The crates.io categories, to browse the crates in one
+    Categories,
+    /// The most used keywords, to browse the crates with one
+    Keywords,
+}
+
+/// A snapshot of where the user was, used to move back and forward between views
+#[derive(Clone, Debug)]
+pub struct NavEntry {
+    pub view: View,
+    pub query: SearchQuery,
+    pub page: u32,
+    pub sort: CratesSort,
+    /// The filters applied to the search results
+    pub filter: SearchFilter,
+    /// The highlighted item, or the first visible line when reading a README
+    pub selection: Option<usize>,
+}
+
+/// Browser-style back and forward history
+#[derive(Default)]
+pub struct NavStack {
+    back: Vec<NavEntry>,
+    forward: Vec<NavEntry>,
+}
+
+impl NavStack {
+    /// Records the entry being navigated away from.  Any forward history is discarded.
+    pub fn push(&mut self, current: NavEntry) {
+        self.back.push(current);
+        self.forward.clear();
+    }
+
+    /// Returns the previous entry, remembering the current one so it can be returned to.
+    pub fn back(&mut self, current: NavEntry) -> Option<NavEntry> {
+        let prev = self.back.pop()?;
+        self.forward.push(current);
+        Some(prev)
+    }
+
+    /// Returns the next entry, remembering the current one so it can be returned to.
+    pub fn forward(&mut self, current: NavEntry) -> Option<NavEntry> {
+        let next = self.forward.pop()?;
+        self.back.push(current);
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str) -> NavEntry {
+        NavEntry {
+            view: View::Details(name.to_string()),
+            query: SearchQuery::default(),
+            page: 1,
+            sort: CratesSort::default(),
+            filter: SearchFilter::default(),
+            selection: None,
+        }
+    }
+
+    fn name(entry: Option<NavEntry>) -> Option<String> {
+        match entry?.view {
+            View::Details(name) => Some(name),
+            view => panic!("unexpected view {:?}", view),
+        }
+    }
+
+    #[test]
+    fn back_and_forward() {
+        let mut nav = NavStack::default();
+        nav.push(entry("a"));
+        nav.push(entry("b"));
+        // at "c"
+        assert_eq!(name(nav.back(entry("c"))), Some("b".into()));
+        assert_eq!(name(nav.back(entry("b"))), Some("a".into()));
+        assert_eq!(name(nav.forward(entry("a"))), Some("b".into()));
+        assert_eq!(name(nav.forward(entry("b"))), Some("c".into()));
+    }
+
+    #[test]
+    fn ends_of_the_stack() {
+        let mut nav = NavStack::default();
+        assert_eq!(name(nav.back(entry("a"))), None);
+        assert_eq!(name(nav.forward(entry("a"))), None);
+
+        nav.push(entry("a"));
+        assert_eq!(name(nav.forward(entry("b"))), None);
+        assert_eq!(name(nav.back(entry("b"))), Some("a".into()));
+        assert_eq!(name(nav.back(entry("a"))), None);
+        // failing to move doesn't lose the way forward
+        assert_eq!(name(nav.forward(entry("a"))), Some("b".into()));
+    }
+
+    #[test]
+    fn push_after_back_drops_forward() {
+        let mut nav = NavStack::default();
+        nav.push(entry("a"));
+        nav.push(entry("b"));
+        assert_eq!(name(nav.back(entry("c"))), Some("b".into()));
+        // from "b" to "d" instead of "c"
+        nav.push(entry("b"));
+        assert_eq!(name(nav.forward(entry("d"))), None);
+        assert_eq!(name(nav.back(entry("d"))), Some("b".into()));
+        assert_eq!(name(nav.back(entry("b"))), Some("a".into()));
+        assert_eq!(name(nav.forward(entry("a"))), Some("b".into()));
+        assert_eq!(name(nav.forward(entry("b"))), Some("d".into()));
+    }
+}
@@ -0,0 +1,31 @@
+use std::{
+    env,
+    io::{self, Write},
+};
+
+/// Sets the clipboard of the terminal emulator with an OSC 52 escape sequence.  This works over
+/// SSH and without a display server, as long as the terminal supports it.
+pub fn copy(text: &str) -> io::Result<()> {
+    let sequence = format!("\x1b]52;c;{}\x07", base64::encode(text));
+    // tmux only forwards escape sequences to the outer terminal when they are wrapped in a
+    // passthrough sequence, with every ESC doubled
+    let sequence = if env::var_os("TMUX").is_some() {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    };
+
+    let mut stdout = io::stdout();
+    stdout.write_all(sequence.as_bytes())?;
+    stdout.flush()
+}
+
+/// Whether a display server is available for the system clipboard.  Only X11 and Wayland need
+/// one, so this is always true on other platforms.
+pub fn has_display() -> bool {
+    if cfg!(all(unix, not(target_os = "macos"))) {
+        env::var_os("DISPLAY").is_some() || env::var_os("WAYLAND_DISPLAY").is_some()
+    } else {
+        true
+    }
+}
@@ -0,0 +1,298 @@
+use semver::{Version, VersionReq};
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+use crate::{crates_io::CrateSearcher, project::Project};
+
+/// How up to date one of the project's dependencies is
+pub struct OutdatedEntry {
+    pub name: String,
+    /// The requirement in the manifest
+    pub req: Option<String>,
+    /// The version in the lockfile
+    pub locked: Option<Version>,
+    /// The newest version the requirement allows
+    pub compatible: Option<Version>,
+    /// The newest stable version
+    pub latest: Option<Version>,
+    /// Whether the locked version has been yanked
+    pub yanked: bool,
+    /// Why the crate couldn't be looked up
+    pub error: Option<String>,
+}
+
+impl OutdatedEntry {
+    /// Whether a newer version than the locked one is available
+    pub fn is_outdated(&self) -> bool {
+        match (&self.locked, &self.latest) {
+            (Some(locked), Some(latest)) => latest > locked,
+            _ => false,
+        }
+    }
+
+    /// A short description of the entry's state
+    pub fn status(&self) -> &'static str {
+        if self.error.is_some() {
+            "unknown"
+        } else if self.yanked {
+            "yanked"
+        } else if self.locked.is_none() {
+            "not locked"
+        } else if self.is_outdated() {
+            "outdated"
+        } else {
+            "up to date"
+        }
+    }
+}
+
+impl Serialize for OutdatedEntry {
+    /// Versions are serialized as strings, along with the status
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let version = |version: &Option<Version>| version.as_ref().map(Version::to_string);
+        let mut entry = serializer.serialize_struct("OutdatedEntry", 8)?;
+        entry.serialize_field("name", &self.name)?;
+        entry.serialize_field("req", &self.req)?;
+        entry.serialize_field("locked", &version(&self.locked))?;
+        entry.serialize_field("compatible", &version(&self.compatible))?;
+        entry.serialize_field("latest", &version(&self.latest))?;
+        entry.serialize_field("status", self.status())?;
+        entry.serialize_field("yanked", &self.yanked)?;
+        entry.serialize_field("error", &self.error)?;
+        entry.end()
+    }
+}
+
+/// Looks up every registry dependency of the project, giving the entries in the order of the
+/// project's dependencies.  Crates that can't be looked up are listed with their error.
+pub fn outdated_report(project: &Project, client: &mut CrateSearcher) -> Vec<OutdatedEntry> {
+    project
+        .direct
+        .iter()
+        .filter(|dep| !dep.local)
+        .map(|dep| {
+            let req = dep
+                .req
+                .as_deref()
+                .and_then(|req| VersionReq::parse(req).ok());
+            let locked_versions = project
+                .get(&dep.name)
+                .map(|dep| dep.locked.as_slice())
+                .unwrap_or_default();
+            // with several versions locked, the one the requirement matches is ours
+            let locked = locked_versions
+                .iter()
+                .filter(|version| req.as_ref().is_none_or(|req| req.matches(version)))
+                .max()
+                .or_else(|| locked_versions.iter().max())
+                .cloned();
+
+            let mut entry = OutdatedEntry {
+                name: dep.name.clone(),
+                req: dep.req.clone(),
+                locked,
+                compatible: None,
+                latest: None,
+                yanked: false,
+                error: None,
+            };
+
+            match client.get_crate_with_cache(&dep.name) {
+                Ok(crte) => {
+                    let versions = crte
+                        .versions
+                        .iter()
+                        .filter_map(|version| {
+                            Some((Version::parse(&version.num).ok()?, version.yanked))
+                        })
+                        .collect::<Vec<_>>();
+                    let available = || {
+                        versions
+                            .iter()
+                            .filter(|(_, yanked)| !yanked)
+                            .map(|(version, _)| version)
+                    };
+
+                    entry.compatible = available()
+                        .filter(|version| req.as_ref().is_some_and(|req| req.matches(version)))
+                        .max()
+                        .cloned();
+                    entry.latest = available()
+                        .filter(|version| version.pre.is_empty())
+                        .max()
+                        .or_else(|| available().max())
+                        .cloned();
+                    entry.yanked = versions
+                        .iter()
+                        .any(|(version, yanked)| *yanked && Some(version) == entry.locked.as_ref());
+                }
+                Err(err) => entry.error = Some(err.to_string()),
+            }
+
+            entry
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crates_io::CrateResponse;
+
+    const MANIFEST: &str = r#"
+[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+anyhow = "1"
+helper = { path = "helper" }
+rand = "0.7"
+serde = "1.0"
+time = "0.3"
+"#;
+
+    const LOCKFILE: &str = r#"
+[[package]]
+name = "rand"
+version = "0.7.3"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "rand"
+version = "0.8.5"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "serde"
+version = "1.0.100"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "time"
+version = "0.3.30"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+
+    /// The crate with its versions, given as the number and whether it's yanked
+    fn response(name: &str, versions: &[(&str, bool)]) -> CrateResponse {
+        let time = "2024-01-01T00:00:00+00:00";
+        let versions = versions
+            .iter()
+            .enumerate()
+            .map(|(id, (num, yanked))| {
+                serde_json::json!({
+                    "id": id, "num": num, "created_at": time, "updated_at": time,
+                    "downloads": 0, "yanked": yanked, "license": null,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::from_value(serde_json::json!({
+            "crate": {
+                "id": name, "name": name, "updated_at": time, "created_at": time,
+                "downloads": 0, "recent_downloads": 0, "max_version": "",
+                "newest_version": "", "description": null, "documentation": null,
+                "repository": null,
+                "links": {
+                    "version_downloads": "", "versions": null, "owners": "",
+                    "owner_team": "", "owner_user": "", "reverse_dependencies": "",
+                },
+            },
+            "versions": versions,
+        }))
+        .unwrap()
+    }
+
+    fn report() -> Vec<OutdatedEntry> {
+        let project = Project::fixture(&[("Cargo.toml", MANIFEST), ("Cargo.lock", LOCKFILE)]);
+        // nothing listens on the discard port, so crates that aren't cached fail to load
+        let mut client = CrateSearcher::new("http://127.0.0.1:9/api/v1", None).unwrap();
+        client.cache_crate(response(
+            "rand",
+            &[
+                ("0.7.3", false),
+                ("0.7.4", true),
+                ("0.8.5", false),
+                ("0.9.0-beta.1", false),
+            ],
+        ));
+        client.cache_crate(response("serde", &[("1.0.100", true), ("1.0.200", false)]));
+        client.cache_crate(response(
+            "time",
+            &[("0.3.30", false), ("0.4.0-alpha.1", false)],
+        ));
+        outdated_report(&project, &mut client)
+    }
+
+    fn versions(entry: &OutdatedEntry) -> [Option<String>; 3] {
+        [&entry.locked, &entry.compatible, &entry.latest]
+            .map(|v| v.as_ref().map(Version::to_string))
+    }
+
+    #[test]
+    fn entries_follow_the_manifest() {
+        let report = report();
+        let names = report
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect::<Vec<_>>();
+        // path dependencies aren't in the registry
+        assert_eq!(names, ["anyhow", "rand", "serde", "time"]);
+    }
+
+    #[test]
+    fn locked_version_matches_the_requirement() {
+        let report = report();
+        let rand = &report[1];
+        // 0.8.5 is locked for another crate, and the yanked 0.7.4 isn't compatible
+        assert_eq!(
+            versions(rand),
+            [
+                Some("0.7.3".into()),
+                Some("0.7.3".into()),
+                Some("0.8.5".into())
+            ]
+        );
+        assert!(rand.is_outdated());
+        assert_eq!(rand.status(), "outdated");
+    }
+
+    #[test]
+    fn yanked_locked_version() {
+        let report = report();
+        let serde = &report[2];
+        assert_eq!(
+            versions(serde),
+            [
+                Some("1.0.100".into()),
+                Some("1.0.200".into()),
+                Some("1.0.200".into())
+            ]
+        );
+        assert!(serde.yanked);
+        assert_eq!(serde.status(), "yanked");
+    }
+
+    #[test]
+    fn prereleases_are_not_latest() {
+        let report = report();
+        let time = &report[3];
+        assert_eq!(
+            versions(time),
+            [
+                Some("0.3.30".into()),
+                Some("0.3.30".into()),
+                Some("0.3.30".into())
+            ]
+        );
+        assert_eq!(time.status(), "up to date");
+    }
+
+    #[test]
+    fn unknown_crate() {
+        let report = report();
+        let anyhow = &report[0];
+        assert_eq!(versions(anyhow), [None, None, None]);
+        assert!(anyhow.error.is_some());
+        assert_eq!(anyhow.status(), "unknown");
+    }
+}
@@ -0,0 +1,740 @@
+use std::{
+    error::Error,
+    fmt,
+    io::{self, Write},
+    marker::PhantomData,
+    str::FromStr,
+};
+
+use comfy_table::{ContentArrangement, Table};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    crates_io::{CrateSearch, CrateVersion, Dependency, Dependent, Owner},
+    format,
+    outdated::OutdatedEntry,
+    TABLE_STYLE,
+};
+
+/// How results are printed outside of the TUI
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    Ndjson,
+    Csv,
+    Tsv,
+    Markdown,
+}
+
+impl OutputFormat {
+    pub const NAMES: [&'static str; 6] = ["table", "json", "ndjson", "csv", "tsv", "markdown"];
+
+    /// Whether the output is meant to be read by other programs rather than people
+    fn is_machine(self) -> bool {
+        !matches!(self, OutputFormat::Table | OutputFormat::Markdown)
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            "ndjson" | "jsonl" => Ok(Self::Ndjson),
+            "csv" => Ok(Self::Csv),
+            "tsv" => Ok(Self::Tsv),
+            "markdown" | "md" => Ok(Self::Markdown),
+            _ => Err(format!("Unknown output format {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutputFormat::Table => "table",
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Tsv => "tsv",
+            OutputFormat::Markdown => "markdown",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Something printed as a row of results.  Fields are named after the keys it is serialized
+/// with, so the same names choose the columns of a table and the keys of a JSON object.
+pub trait Record: Serialize {
+    /// Every field with its column header
+    const FIELDS: &'static [(&'static str, &'static str)];
+    /// The fields printed when none are chosen
+    const DEFAULT_FIELDS: &'static [&'static str];
+
+    /// The field's value as text.  Machine readable values are given for JSON, CSV and TSV.
+    fn value(&self, field: &str, machine: bool) -> String;
+}
+
+/// Prints the records in the format.  Only the chosen fields are printed, in their order.  When
+/// none are chosen, tables get the default fields and JSON gets whole records.
+pub fn print_records<R: Record>(
+    records: &[R],
+    format: OutputFormat,
+    fields: &[String],
+) -> Result<(), Box<dyn Error>> {
+    let mut writer = RecordWriter::new(format, fields)?;
+    writer.write(records)?;
+    writer.finish()
+}
+
+/// Prints a single record.  Tables list its fields one per row, every field when none are
+/// chosen, and JSON gets an object rather than an array.
+pub fn print_record<R: Record>(
+    record: &R,
+    format: OutputFormat,
+    fields: &[String],
+) -> Result<(), Box<dyn Error>> {
+    let chosen = !fields.is_empty();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let written = match format {
+        OutputFormat::Table | OutputFormat::Markdown => {
+            let fields = if chosen {
+                select_fields::<R>(fields)?
+            } else {
+                R::FIELDS.iter().map(|(name, _)| *name).collect()
+            };
+            let rows = fields
+                .iter()
+                .map(|field| vec![header::<R>(field).to_string(), record.value(field, false)])
+                .collect();
+            write_table(&mut out, format, vec!["Field".into(), "Value".into()], rows)
+        }
+        OutputFormat::Json => {
+            let fields = select_fields::<R>(fields)?;
+            json_value(record, &fields, chosen)
+                .and_then(|value| serde_json::to_string_pretty(&value))
+                .map_err(io::Error::from)
+                .and_then(|json| writeln!(out, "{}", json))
+        }
+        _ => return print_records(std::slice::from_ref(record), format, fields),
+    };
+    match written {
+        // the output was piped to something like `head` that stopped reading
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        written => Ok(written?),
+    }
+}
+
+/// Prints records as they arrive, such as a page of results at a time.  Tables are sized to fit
+/// every row, so they are printed once all of the records have been written.
+pub struct RecordWriter<R> {
+    format: OutputFormat,
+    fields: Vec<&'static str>,
+    /// whether fields were chosen rather than defaulted
+    chosen: bool,
+    /// whether the header, or the start of the JSON array, has been printed
+    started: bool,
+    /// whether the output was closed by whatever it's piped to
+    closed: bool,
+    /// the rows of a table, printed when finished
+    rows: Vec<Vec<String>>,
+    record: PhantomData<R>,
+}
+
+impl<R: Record> RecordWriter<R> {
+    pub fn new(format: OutputFormat, fields: &[String]) -> Result<Self, String> {
+        Ok(Self {
+            format,
+            fields: select_fields::<R>(fields)?,
+            chosen: !fields.is_empty(),
+            started: false,
+            closed: false,
+            rows: Vec::new(),
+            record: PhantomData,
+        })
+    }
+
+    /// Whether the output was closed, e.g. by `head`, so nothing more will be printed
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn write(&mut self, records: &[R]) -> Result<(), Box<dyn Error>> {
+        let stdout = io::stdout();
+        let written = self.write_to(&mut stdout.lock(), records);
+        self.check(written)
+    }
+
+    /// Prints whatever is left: the table, the end of the JSON array, or the header when there
+    /// were no records
+    pub fn finish(mut self) -> Result<(), Box<dyn Error>> {
+        let stdout = io::stdout();
+        let written = self.finish_to(&mut stdout.lock());
+        self.check(written)
+    }
+
+    fn finish_to<W: Write>(&mut self, out: &mut W) -> io::Result<()> {
+        match self.format {
+            _ if self.closed => Ok(()),
+            OutputFormat::Table => {
+                let rows = std::mem::take(&mut self.rows);
+                write_table(out, self.format, self.headers(), rows)
+            }
+            OutputFormat::Json if self.started => writeln!(out, "\n]"),
+            OutputFormat::Json => writeln!(out, "[]"),
+            _ if self.started => Ok(()),
+            _ => self.write_to(out, &[]),
+        }
+    }
+
+    fn check(&mut self, written: io::Result<()>) -> Result<(), Box<dyn Error>> {
+        match written {
+            // the output was piped to something like `head` that stopped reading
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {
+                self.closed = true;
+                Ok(())
+            }
+            written => Ok(written?),
+        }
+    }
+
+    fn headers(&self) -> Vec<String> {
+        let machine = self.format.is_machine();
+        self.fields
+            .iter()
+            .map(|field| if machine { field } else { header::<R>(field) }.to_string())
+            .collect()
+    }
+
+    fn write_to<W: Write>(&mut self, out: &mut W, records: &[R]) -> io::Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+        let machine = self.format.is_machine();
+        let mut rows = records
+            .iter()
+            .map(|record| {
+                self.fields
+                    .iter()
+                    .map(|field| record.value(field, machine))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let has_header = matches!(
+            self.format,
+            OutputFormat::Csv | OutputFormat::Tsv | OutputFormat::Markdown
+        );
+        if !self.started && has_header {
+            let mut headers = vec![self.headers()];
+            if self.format == OutputFormat::Markdown {
+                headers.push(vec!["---".to_string(); self.fields.len()]);
+            }
+            rows.splice(0..0, headers);
+        }
+
+        match self.format {
+            OutputFormat::Table => self.rows.extend(rows),
+            OutputFormat::Json => {
+                for record in records {
+                    let value = json_value(record, &self.fields, self.chosen)?;
+                    let json = serde_json::to_string_pretty(&value)?.replace('\n', "\n  ");
+                    let separator = if self.started { ",\n" } else { "[\n" };
+                    write!(out, "{}  {}", separator, json)?;
+                    self.started = true;
+                }
+            }
+            OutputFormat::Ndjson => {
+                for record in records {
+                    writeln!(out, "{}", json_value(record, &self.fields, self.chosen)?)?;
+                }
+            }
+            OutputFormat::Csv => {
+                for row in rows {
+                    let cells = row.iter().map(|cell| csv_escape(cell)).collect::<Vec<_>>();
+                    writeln!(out, "{}", cells.join(","))?;
+                }
+            }
+            OutputFormat::Tsv => {
+                for row in rows {
+                    let cells = row.iter().map(|cell| tsv_escape(cell)).collect::<Vec<_>>();
+                    writeln!(out, "{}", cells.join("\t"))?;
+                }
+            }
+            OutputFormat::Markdown => {
+                for row in rows {
+                    let cells = row
+                        .iter()
+                        .map(|cell| markdown_escape(cell))
+                        .collect::<Vec<_>>();
+                    writeln!(out, "| {} |", cells.join(" | "))?;
+                }
+            }
+        }
+        if self.format != OutputFormat::Json {
+            self.started = true;
+        }
+        out.flush()
+    }
+}
+
+/// Writes a table for people to read, either drawn or in Markdown
+fn write_table<W: Write>(
+    out: &mut W,
+    format: OutputFormat,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+) -> io::Result<()> {
+    if format == OutputFormat::Markdown {
+        let divider = vec!["---".to_string(); headers.len()];
+        for row in [headers, divider].into_iter().chain(rows) {
+            let cells = row
+                .iter()
+                .map(|cell| markdown_escape(cell))
+                .collect::<Vec<_>>();
+            writeln!(out, "| {} |", cells.join(" | "))?;
+        }
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.load_preset(TABLE_STYLE);
+    table.set_header(headers);
+    for row in rows {
+        table.add_row(row);
+    }
+    writeln!(out, "{}", table)
+}
+
+/// Checks the chosen fields against the record's, defaulting to its default fields
+fn select_fields<R: Record>(fields: &[String]) -> Result<Vec<&'static str>, String> {
+    if fields.is_empty() {
+        return Ok(R::DEFAULT_FIELDS.to_vec());
+    }
+    fields
+        .iter()
+        .map(|field| {
+            R::FIELDS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(field.trim()))
+                .map(|(name, _)| *name)
+                .ok_or_else(|| {
+                    let names = R::FIELDS.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+                    format!("Unknown field {}.  Use one of {}", field, names.join(", "))
+                })
+        })
+        .collect()
+}
+
+fn header<R: Record>(field: &str) -> &'static str {
+    R::FIELDS
+        .iter()
+        .find(|(name, _)| *name == field)
+        .map_or("", |(_, header)| *header)
+}
+
+/// The record serialized with only the fields, in their order, or whole when none were chosen
+fn json_value<R: Record>(
+    record: &R,
+    fields: &[&str],
+    chosen: bool,
+) -> Result<Value, serde_json::Error> {
+    let mut value = serde_json::to_value(record)?;
+    if !chosen {
+        return Ok(value);
+    }
+    let mut selected = Map::new();
+    if let Some(object) = value.as_object_mut() {
+        for field in fields {
+            if let Some(field_value) = object.remove(*field) {
+                selected.insert(field.to_string(), field_value);
+            }
+        }
+    }
+    Ok(Value::Object(selected))
+}
+
+/// Quotes the value when it contains a separator, quote or line break, as in RFC 4180
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// TSV has no quoting, so tabs and line breaks become spaces
+fn tsv_escape(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+fn markdown_escape(value: &str) -> String {
+    value.replace('|', "\\|").replace(['\n', '\r'], " ")
+}
+
+impl Record for CrateSearch {
+    const FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("id", "ID"),
+        ("name", "Name"),
+        ("created_at", "Created"),
+        ("updated_at", "Updated"),
+        ("downloads", "Downloads"),
+        ("recent_downloads", "Recent Downloads"),
+        ("max_version", "Max Version"),
+        ("newest_version", "Newest Version"),
+        ("description", "Description"),
+        ("documentation", "Documentation"),
+        ("repository", "Repository"),
+        ("homepage", "Homepage"),
+        ("exact_match", "Exact Match"),
+        ("yanked", "Yanked"),
+    ];
+    const DEFAULT_FIELDS: &'static [&'static str] = &[
+        "name",
+        "created_at",
+        "updated_at",
+        "downloads",
+        "recent_downloads",
+        "max_version",
+        "newest_version",
+        "description",
+    ];
+
+    fn value(&self, field: &str, machine: bool) -> String {
+        let date = |date| format_date(date, machine);
+        match field {
+            "id" => self.id.clone(),
+            "name" => self.name.clone(),
+            "created_at" => date(&self.created_at),
+            "updated_at" => date(&self.updated_at),
+            "downloads" => format_count(self.downloads, machine),
+            "recent_downloads" => format_count(self.recent_downloads, machine),
+            "max_version" => self.max_version.clone(),
+            "newest_version" => self.newest_version.clone(),
+            "description" => text(&self.description),
+            "documentation" => text(&self.documentation),
+            "repository" => text(&self.repository),
+            "homepage" => text(&self.homepage),
+            "exact_match" => self.exact_match.to_string(),
+            "yanked" => self.yanked.to_string(),
+            _ => String::new(),
+        }
+    }
+}
+
+impl Record for CrateVersion {
+    const FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("id", "ID"),
+        ("num", "Version"),
+        ("created_at", "Released"),
+        ("updated_at", "Updated"),
+        ("downloads", "Downloads"),
+        ("yanked", "Yanked"),
+        ("license", "License"),
+        ("rust_version", "MSRV"),
+        ("crate_size", "Size"),
+        ("features", "Features"),
+    ];
+    const DEFAULT_FIELDS: &'static [&'static str] = &[
+        "num",
+        "created_at",
+        "downloads",
+        "yanked",
+        "license",
+        "rust_version",
+    ];
+
+    fn value(&self, field: &str, machine: bool) -> String {
+        match field {
+            "id" => self.id.to_string(),
+            "num" => self.num.clone(),
+            "created_at" => format_date(&self.created_at, machine),
+            "updated_at" => format_date(&self.updated_at, machine),
+            "downloads" => format_count(self.downloads, machine),
+            "yanked" => self.yanked.to_string(),
+            "license" => text(&self.license),
+            "rust_version" => text(&self.rust_version),
+            "crate_size" => self
+                .crate_size
+                .map_or_else(String::new, |size| size.to_string()),
+            "features" => self.features.keys().cloned().collect::<Vec<_>>().join(" "),
+            _ => String::new(),
+        }
+    }
+}
+
+impl Record for Dependency {
+    const FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("crate_id", "Name"),
+        ("req", "Requirement"),
+        ("kind", "Kind"),
+        ("optional", "Optional"),
+        ("default_features", "Default Features"),
+        ("features", "Features"),
+        ("target", "Target"),
+    ];
+    const DEFAULT_FIELDS: &'static [&'static str] =
+        &["crate_id", "req", "kind", "optional", "features", "target"];
+
+    fn value(&self, field: &str, _machine: bool) -> String {
+        match field {
+            "crate_id" => self.crate_id.clone(),
+            "req" => self.req.clone(),
+            "kind" => self.kind.clone(),
+            "optional" => self.optional.to_string(),
+            "default_features" => self.default_features.to_string(),
+            "features" => self.features.join(" "),
+            "target" => text(&self.target),
+            _ => String::new(),
+        }
+    }
+}
+
+impl Record for Dependent {
+    const FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("name", "Name"),
+        ("version", "Version"),
+        ("req", "Requirement"),
+        ("kind", "Kind"),
+        ("optional", "Optional"),
+        ("downloads", "Downloads"),
+    ];
+    const DEFAULT_FIELDS: &'static [&'static str] =
+        &["name", "version", "req", "kind", "optional", "downloads"];
+
+    fn value(&self, field: &str, machine: bool) -> String {
+        match field {
+            "name" => self.name.clone(),
+            "version" => self.version.clone(),
+            "req" => self.req.clone(),
+            "kind" => self.kind.clone(),
+            "optional" => self.optional.to_string(),
+            "downloads" => format_count(self.downloads, machine),
+            _ => String::new(),
+        }
+    }
+}
+
+impl Record for Owner {
+    const FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("id", "ID"),
+        ("login", "Login"),
+        ("name", "Name"),
+        ("kind", "Kind"),
+        ("url", "URL"),
+    ];
+    const DEFAULT_FIELDS: &'static [&'static str] = &["login", "name", "kind", "url"];
+
+    fn value(&self, field: &str, _machine: bool) -> String {
+        match field {
+            "id" => self.id.to_string(),
+            "login" => self.login.clone(),
+            "name" => text(&self.name),
+            "kind" => self.kind.clone(),
+            "url" => text(&self.url),
+            _ => String::new(),
+        }
+    }
+}
+
+impl Record for OutdatedEntry {
+    const FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("name", "Name"),
+        ("req", "Requirement"),
+        ("locked", "Locked"),
+        ("compatible", "Compatible"),
+        ("latest", "Latest"),
+        ("status", "Status"),
+        ("yanked", "Yanked"),
+        ("error", "Error"),
+    ];
+    const DEFAULT_FIELDS: &'static [&'static str] =
+        &["name", "req", "locked", "compatible", "latest", "status"];
+
+    fn value(&self, field: &str, machine: bool) -> String {
+        // tables mark what's missing, rather than leaving a gap
+        let missing = if machine { "" } else { "-" };
+        let version = |version: &Option<semver::Version>| {
+            version
+                .as_ref()
+                .map_or_else(|| missing.to_string(), |version| version.to_string())
+        };
+        match field {
+            "name" => self.name.clone(),
+            "req" => self.req.clone().unwrap_or_else(|| missing.to_string()),
+            "locked" => version(&self.locked),
+            "compatible" => version(&self.compatible),
+            "latest" => version(&self.latest),
+            "status" => match &self.error {
+                Some(err) if !machine => format!("{}: {}", self.status(), err),
+                _ => self.status().to_string(),
+            },
+            "yanked" => self.yanked.to_string(),
+            "error" => text(&self.error),
+            _ => String::new(),
+        }
+    }
+}
+
+fn format_date(date: &DateTime<Local>, machine: bool) -> String {
+    if machine {
+        date.to_rfc3339()
+    } else {
+        format::date(date)
+    }
+}
+
+fn format_count(count: u64, machine: bool) -> String {
+    if machine {
+        count.to_string()
+    } else {
+        format::count(count)
+    }
+}
+
+fn text(text: &Option<String>) -> String {
+    text.clone().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owner(id: u64, login: &str, name: Option<&str>) -> Owner {
+        Owner {
+            id,
+            login: login.to_string(),
+            name: name.map(str::to_string),
+            kind: "user".to_string(),
+            url: None,
+        }
+    }
+
+    /// Writes each batch with its own `write`, the way pages of results arrive.
+    fn output(format: OutputFormat, fields: &[&str], batches: &[Vec<Owner>]) -> String {
+        let fields = fields
+            .iter()
+            .map(|field| field.to_string())
+            .collect::<Vec<_>>();
+        let mut writer = RecordWriter::<Owner>::new(format, &fields).unwrap();
+        let mut out = Vec::new();
+        for batch in batches {
+            writer.write_to(&mut out, batch).unwrap();
+        }
+        writer.finish_to(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(csv_escape("plain"), "plain");
+        assert_eq!(csv_escape("a,b"), "\"a,b\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_escape("two\nlines"), "\"two\nlines\"");
+        assert_eq!(tsv_escape("a\tb\nc\r\nd"), "a b c  d");
+        assert_eq!(markdown_escape("a|b\nc"), "a\\|b c");
+    }
+
+    #[test]
+    fn csv_records() {
+        let owners = vec![
+            owner(1, "dtolnay", Some("David, T")),
+            owner(2, "ghost", None),
+        ];
+        assert_eq!(
+            output(OutputFormat::Csv, &["login", "name"], &[owners]),
+            "login,name\ndtolnay,\"David, T\"\nghost,\n"
+        );
+        assert_eq!(output(OutputFormat::Csv, &["id"], &[]), "id\n");
+    }
+
+    #[test]
+    fn tsv_and_markdown_records() {
+        let owners = vec![owner(1, "a", Some("tab\there | pipe"))];
+        assert_eq!(
+            output(OutputFormat::Tsv, &["login", "name"], &[owners]),
+            "login\tname\na\ttab here | pipe\n"
+        );
+        let owners = vec![owner(1, "a", Some("tab\there | pipe"))];
+        assert_eq!(
+            output(OutputFormat::Markdown, &["login", "name"], &[owners]),
+            "| Login | Name |\n| --- | --- |\n| a | tab\there \\| pipe |\n"
+        );
+    }
+
+    #[test]
+    fn field_selection() {
+        let fields = |fields: &[&str]| {
+            let fields = fields
+                .iter()
+                .map(|field| field.to_string())
+                .collect::<Vec<_>>();
+            select_fields::<Owner>(&fields)
+        };
+        assert_eq!(fields(&[]), Ok(Owner::DEFAULT_FIELDS.to_vec()));
+        assert_eq!(
+            fields(&["url", " LOGIN ", "id"]),
+            Ok(vec!["url", "login", "id"])
+        );
+        let err = fields(&["login", "email"]).unwrap_err();
+        assert!(
+            err.starts_with("Unknown field email.  Use one of id, login"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn json_fields_keep_their_order() {
+        let json = output(
+            OutputFormat::Ndjson,
+            &["login", "id"],
+            &[vec![owner(7, "a", None)]],
+        );
+        assert_eq!(json, "{\"login\":\"a\",\"id\":7}\n");
+        let json = output(OutputFormat::Ndjson, &[], &[vec![owner(7, "a", None)]]);
+        assert_eq!(
+            json,
+            "{\"id\":7,\"login\":\"a\",\"name\":null,\"kind\":\"user\",\"url\":null}\n"
+        );
+    }
+
+    #[test]
+    fn json_array_framing() {
+        let json = |batches: &[Vec<Owner>]| {
+            let json = output(OutputFormat::Json, &["id"], batches);
+            let value = serde_json::from_str::<Value>(&json).unwrap();
+            (json, value)
+        };
+
+        let (none, value) = json(&[]);
+        assert_eq!(none, "[]\n");
+        assert_eq!(value, serde_json::json!([]));
+
+        let (_, value) = json(&[vec![], vec![]]);
+        assert_eq!(value, serde_json::json!([]));
+
+        let (one, value) = json(&[vec![owner(1, "a", None)]]);
+        assert_eq!(one, "[\n  {\n    \"id\": 1\n  }\n]\n");
+        assert_eq!(value, serde_json::json!([{ "id": 1 }]));
+
+        let batches = [
+            vec![owner(1, "a", None), owner(2, "b", None)],
+            vec![],
+            vec![owner(3, "c", None)],
+        ];
+        let (_, value) = json(&batches);
+        assert_eq!(
+            value,
+            serde_json::json!([{ "id": 1 }, { "id": 2 }, { "id": 3 }])
+        );
+    }
+}
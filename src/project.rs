@@ -0,0 +1,293 @@
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+use semver::Version;
+use serde::Deserialize;
+
+use crate::manifest::{Manifest, ManifestDep};
+
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+/// A package in `Cargo.lock`
+#[derive(Clone, Debug, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    /// Where the package comes from.  None for the workspace's own packages.
+    pub source: Option<String>,
+}
+
+impl LockedPackage {
+    /// Whether the package comes from a registry, rather than the workspace, a path or git
+    pub fn is_registry(&self) -> bool {
+        self.source
+            .as_deref()
+            .is_some_and(|source| source.starts_with("registry+") || source.starts_with("sparse+"))
+    }
+}
+
+/// How the project in the working directory uses a crate
+#[derive(Debug, Default)]
+pub struct ProjectDep {
+    /// Whether a manifest depends on the crate, rather than only another dependency
+    pub direct: bool,
+    /// The versions in the lockfile.  There can be more than one when crates need incompatible
+    /// versions.
+    pub locked: Vec<Version>,
+}
+
+impl ProjectDep {
+    /// The newest locked version
+    pub fn locked_version(&self) -> Option<&Version> {
+        self.locked.iter().max()
+    }
+
+    /// Whether the version is newer than every locked version
+    pub fn is_outdated(&self, version: &str) -> bool {
+        match (self.locked_version(), Version::parse(version)) {
+            (Some(locked), Ok(version)) => version > *locked,
+            _ => false,
+        }
+    }
+}
+
+/// The Cargo project in the working directory, used to point out crates it already uses
+pub struct Project {
+    /// The dependencies declared in the manifests, with inherited requirements filled in from
+    /// the workspace.  Each name and requirement is only listed once.
+    pub direct: Vec<ManifestDep>,
+    deps: HashMap<String, ProjectDep>,
+}
+
+impl Project {
+    /// Reads the manifests and lockfile of the project the directory is in.  None if it isn't in
+    /// a Cargo project.
+    pub fn detect(dir: &Path) -> Option<Self> {
+        let manifests = Manifest::candidates(dir).ok()?;
+        let lockfile = dir
+            .ancestors()
+            .map(|dir| dir.join("Cargo.lock"))
+            .find(|path| path.is_file());
+        let packages = lockfile
+            .as_deref()
+            .and_then(|path| Self::read_lockfile(path).ok())
+            .unwrap_or_default();
+
+        let workspace_deps = manifests
+            .first()
+            .and_then(|path| Manifest::workspace_root(path))
+            .map(|root| root.workspace_dependencies())
+            .unwrap_or_default();
+
+        let mut direct: Vec<ManifestDep> = Vec::new();
+        let mut deps: HashMap<String, ProjectDep> = HashMap::new();
+        for manifest in manifests
+            .iter()
+            .filter_map(|path| Manifest::load(path).ok())
+        {
+            for mut dep in manifest.dependencies() {
+                if dep.inherited {
+                    if let Some(shared) = workspace_deps.iter().find(|d| d.name == dep.name) {
+                        dep.req = shared.req.clone();
+                        dep.local = shared.local;
+                    }
+                }
+                deps.entry(dep.name.clone()).or_default().direct = true;
+                if !direct
+                    .iter()
+                    .any(|d| d.name == dep.name && d.req == dep.req)
+                {
+                    direct.push(dep);
+                }
+            }
+        }
+        direct.sort_by(|a, b| a.name.cmp(&b.name));
+        for package in packages.iter().filter(|package| package.is_registry()) {
+            if let Ok(version) = Version::parse(&package.version) {
+                deps.entry(package.name.clone())
+                    .or_default()
+                    .locked
+                    .push(version);
+            }
+        }
+
+        Some(Self { direct, deps })
+    }
+
+    fn read_lockfile(path: &Path) -> Result<Vec<LockedPackage>, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)?;
+        Ok(toml::from_str::<Lockfile>(&contents)?.package)
+    }
+
+    /// How the project uses the crate.  None if it isn't used at all.
+    pub fn get(&self, name: &str) -> Option<&ProjectDep> {
+        self.deps.get(name)
+    }
+}
+
+#[cfg(test)]
+impl Project {
+    /// Writes the files, given by their paths relative to the project, to a new directory and
+    /// detects the project in it
+    pub fn fixture(files: &[(&str, &str)]) -> Self {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "cratuity-project-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        for (path, contents) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        let project = Self::detect(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        project.expect("no project in the fixture")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKSPACE: &str = r#"
+[workspace]
+members = ["crates/*"]
+
+[workspace.dependencies]
+serde = { version = "1.0", features = ["derive"] }
+"#;
+
+    const APP: &str = r#"
+[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+serde = { workspace = true }
+rand = "0.7"
+helper = { path = "../helper" }
+rng = { package = "rand", version = "0.8" }
+
+[dev-dependencies]
+serde = { workspace = true }
+"#;
+
+    const HELPER: &str = r#"
+[package]
+name = "helper"
+version = "0.1.0"
+
+[dependencies]
+rand = "0.8"
+"#;
+
+    const LOCKFILE: &str = r#"
+version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+
+[[package]]
+name = "helper"
+version = "0.1.0"
+
+[[package]]
+name = "libc"
+version = "0.2.150"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "rand"
+version = "0.7.3"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "rand"
+version = "0.8.5"
+source = "sparse+https://index.crates.io/"
+
+[[package]]
+name = "serde"
+version = "1.0.100"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "vendored"
+version = "1.0.0"
+source = "git+https://github.com/example/vendored#0123456"
+"#;
+
+    fn project() -> Project {
+        Project::fixture(&[
+            ("Cargo.toml", WORKSPACE),
+            ("crates/app/Cargo.toml", APP),
+            ("crates/helper/Cargo.toml", HELPER),
+            ("Cargo.lock", LOCKFILE),
+        ])
+    }
+
+    #[test]
+    fn direct_dependencies() {
+        let project = project();
+        let direct = project
+            .direct
+            .iter()
+            .map(|dep| (dep.name.as_str(), dep.req.as_deref(), dep.local))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            direct,
+            [
+                ("helper", None, true),
+                ("rand", Some("0.7"), false),
+                ("rand", Some("0.8"), false),
+                ("serde", Some("1.0"), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn locked_versions() {
+        let project = project();
+        let locked = |name: &str| {
+            project.get(name).map(|dep| {
+                let versions = dep
+                    .locked
+                    .iter()
+                    .map(Version::to_string)
+                    .collect::<Vec<_>>();
+                (dep.direct, versions)
+            })
+        };
+        assert_eq!(
+            locked("rand"),
+            Some((true, vec!["0.7.3".into(), "0.8.5".into()]))
+        );
+        assert_eq!(locked("serde"), Some((true, vec!["1.0.100".into()])));
+        assert_eq!(locked("libc"), Some((false, vec!["0.2.150".into()])));
+        // only registry packages are locked
+        assert_eq!(locked("helper"), Some((true, vec![])));
+        assert_eq!(locked("vendored"), None);
+        assert_eq!(locked("tokio"), None);
+
+        let rand = project.get("rand").unwrap();
+        assert_eq!(rand.locked_version(), Some(&Version::new(0, 8, 5)));
+        assert!(rand.is_outdated("0.9.0"));
+        assert!(!rand.is_outdated("0.8.5"));
+        assert!(!rand.is_outdated("not a version"));
+    }
+
+    #[test]
+    fn package_without_lockfile() {
+        let project = Project::fixture(&[("Cargo.toml", HELPER)]);
+        assert_eq!(project.direct.len(), 1);
+        assert_eq!(project.get("rand").unwrap().locked_version(), None);
+    }
+}
@@ -0,0 +1,505 @@
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Span, Spans, Text},
+};
+
+/// Converts a crate's README into styled text.  crates.io serves READMEs already rendered to
+/// HTML, but plain Markdown is handled too in case the README was not rendered.
+pub fn readme_to_text(readme: &str) -> Text<'static> {
+    if readme.trim_start().starts_with('<') {
+        HtmlRenderer::default().render(readme)
+    } else {
+        markdown_to_text(readme)
+    }
+}
+
+fn heading_style(level: usize) -> Style {
+    let color = if level == 1 {
+        Color::Magenta
+    } else {
+        Color::Cyan
+    };
+    Style::default().fg(color).add_modifier(Modifier::BOLD)
+}
+
+fn code_style() -> Style {
+    Style::default().fg(Color::Yellow)
+}
+
+fn link_style() -> Style {
+    Style::default()
+        .fg(Color::Blue)
+        .add_modifier(Modifier::UNDERLINED)
+}
+
+/// A minimal Markdown renderer: headings, code fences and list bullets.  Everything else is shown
+/// as written.
+fn markdown_to_text(markdown: &str) -> Text<'static> {
+    let mut lines = Vec::new();
+    let mut in_code = false;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+
+        let spans = if in_code {
+            Spans::from(Span::styled(format!("    {}", line), code_style()))
+        } else if trimmed.starts_with('#') {
+            let level = trimmed.chars().take_while(|c| *c == '#').count();
+            Spans::from(Span::styled(trimmed.to_string(), heading_style(level)))
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            let indent = line.len() - trimmed.len();
+            Spans::from(format!("{}• {}", " ".repeat(indent), item))
+        } else {
+            Spans::from(line.to_string())
+        };
+        lines.push(spans);
+    }
+
+    Text::from(lines)
+}
+
+/// Decodes the HTML character references that show up in rendered READMEs
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.char_indices().take(10).find(|(_, c)| *c == ';') {
+            Some((end, _)) => end,
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Whether the text starts with a tag, rather than a `<` such as the one in `1 < 2`
+fn starts_tag(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next() == Some('<')
+        && chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '/' || c == '!')
+}
+
+/// Gets the value of an attribute from the inside of a tag, e.g. `a href="..."`
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    // ascii lowercase keeps the byte offsets the same as the original tag
+    let lower = tag.to_ascii_lowercase();
+    let mut search = 0;
+    while let Some(pos) = lower[search..].find(name) {
+        let start = search + pos;
+        search = start + name.len();
+        // make sure this is the whole attribute name, not the end of another one
+        if !lower[..start].ends_with(char::is_whitespace) {
+            continue;
+        }
+        let rest = tag[search..].trim_start();
+        let rest = match rest.strip_prefix('=') {
+            Some(rest) => rest.trim_start(),
+            None => continue,
+        };
+        let value = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => rest[1..].split(quote).next().unwrap_or(""),
+            _ => rest.split(char::is_whitespace).next().unwrap_or(""),
+        };
+        return Some(decode_entities(value));
+    }
+    None
+}
+
+#[derive(Default)]
+struct HtmlRenderer {
+    lines: Vec<Spans<'static>>,
+    current: Vec<Span<'static>>,
+    /// Styles of the currently open inline elements
+    styles: Vec<Style>,
+    /// The open lists; `Some` holds the next number of an ordered list
+    lists: Vec<Option<u32>>,
+    /// Links in the order they appear, listed at the end of the README
+    links: Vec<String>,
+    /// The target of the link currently being rendered
+    link: Option<String>,
+    /// Whether a space should be added before the next word
+    pending_space: bool,
+    /// Whether anything other than indentation or a bullet is on the current line
+    line_has_text: bool,
+    in_pre: bool,
+    quote_depth: usize,
+    /// Inside a `<script>` or `<style>` element, whose contents are not shown
+    skipping: bool,
+}
+
+impl HtmlRenderer {
+    fn render(mut self, html: &str) -> Text<'static> {
+        let mut rest = html;
+        while !rest.is_empty() {
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            } else if let Some(end) = starts_tag(rest).then(|| rest.find('>')).flatten() {
+                self.tag(&rest[1..end]);
+                rest = &rest[end + 1..];
+            } else {
+                // a '<' that does not start a tag is shown as text
+                let end = rest
+                    .char_indices()
+                    .skip(1)
+                    .find(|(_, c)| *c == '<')
+                    .map_or(rest.len(), |(end, _)| end);
+                if !self.skipping {
+                    self.text(&decode_entities(&rest[..end]));
+                }
+                rest = &rest[end..];
+            }
+        }
+        self.end_line();
+
+        if !self.links.is_empty() {
+            self.blank_line();
+            self.lines
+                .push(Spans::from(Span::styled("Links", heading_style(2))));
+            for (i, link) in self.links.iter().enumerate() {
+                self.lines.push(Spans::from(vec![
+                    Span::raw(format!("[{}] ", i + 1)),
+                    Span::styled(link.clone(), link_style()),
+                ]));
+            }
+        }
+
+        Text::from(self.lines)
+    }
+
+    fn style(&self) -> Style {
+        self.styles
+            .iter()
+            .fold(Style::default(), |acc, style| acc.patch(*style))
+    }
+
+    /// Finishes the current line, if anything has been written to it
+    fn end_line(&mut self) {
+        if !self.current.is_empty() {
+            self.lines
+                .push(Spans::from(std::mem::take(&mut self.current)));
+        }
+        self.pending_space = false;
+        self.line_has_text = false;
+    }
+
+    /// Finishes the current line and makes sure it is followed by exactly one blank line
+    fn blank_line(&mut self) {
+        self.end_line();
+        if self.lines.last().is_some_and(|line| line.width() > 0) {
+            self.lines.push(Spans::default());
+        }
+    }
+
+    /// Starts a line with the quote and list indentation
+    fn start_line(&mut self) {
+        if self.current.is_empty() && self.quote_depth > 0 {
+            self.current.push(Span::styled(
+                "│ ".repeat(self.quote_depth),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+    }
+
+    fn push(&mut self, text: String, style: Style) {
+        self.start_line();
+        self.current.push(Span::styled(text, style));
+        self.line_has_text = true;
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.in_pre {
+            let style = code_style();
+            for (i, line) in text.split('\n').enumerate() {
+                if i > 0 {
+                    // keep blank lines inside code blocks
+                    if self.current.is_empty() {
+                        self.lines.push(Spans::default());
+                    }
+                    self.end_line();
+                }
+                if !line.is_empty() {
+                    let indent = if self.current.is_empty() { "    " } else { "" };
+                    self.push(format!("{}{}", indent, line), style);
+                }
+            }
+            return;
+        }
+
+        let style = self.style();
+        let starts_with_space = text.starts_with(char::is_whitespace);
+        let ends_with_space = text.ends_with(char::is_whitespace);
+        let words = text.split_whitespace().collect::<Vec<_>>();
+        if words.is_empty() {
+            self.pending_space |= starts_with_space && !self.current.is_empty();
+            return;
+        }
+
+        let mut out = String::new();
+        if (self.pending_space || starts_with_space) && self.line_has_text {
+            out.push(' ');
+        }
+        out.push_str(&words.join(" "));
+        self.push(out, style);
+        self.pending_space = ends_with_space;
+    }
+
+    fn tag(&mut self, tag: &str) {
+        let closing = tag.starts_with('/');
+        let tag = tag.trim_start_matches('/').trim_end_matches('/');
+        let name = tag
+            .split(|c: char| c.is_whitespace())
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+
+        match (name.as_str(), closing) {
+            ("script" | "style", closing) => self.skipping = !closing,
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                let level = name[1..].parse().unwrap_or(1);
+                self.blank_line();
+                self.styles.push(heading_style(level));
+                self.push(format!("{} ", "#".repeat(level)), heading_style(level));
+            }
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", true) => {
+                self.styles.pop();
+                self.blank_line();
+            }
+            ("p" | "div" | "details" | "summary", _) => self.blank_line(),
+            ("br", _) => self.end_line(),
+            ("hr", _) => {
+                self.blank_line();
+                self.lines.push(Spans::from(Span::styled(
+                    "─".repeat(40),
+                    Style::default().fg(Color::DarkGray),
+                )));
+                self.lines.push(Spans::default());
+            }
+            ("pre", false) => {
+                self.blank_line();
+                self.in_pre = true;
+            }
+            ("pre", true) => {
+                self.in_pre = false;
+                self.blank_line();
+            }
+            ("code", false) if !self.in_pre => self.styles.push(code_style()),
+            ("code", true) if !self.in_pre => {
+                self.styles.pop();
+            }
+            ("strong" | "b", false) => self
+                .styles
+                .push(Style::default().add_modifier(Modifier::BOLD)),
+            ("em" | "i", false) => self
+                .styles
+                .push(Style::default().add_modifier(Modifier::ITALIC)),
+            ("del" | "s", false) => self
+                .styles
+                .push(Style::default().add_modifier(Modifier::CROSSED_OUT)),
+            ("strong" | "b" | "em" | "i" | "del" | "s", true) => {
+                self.styles.pop();
+            }
+            ("a", false) => {
+                self.link = attribute(tag, "href").filter(|href| !href.starts_with('#'));
+                self.styles.push(link_style());
+            }
+            ("a", true) => {
+                self.styles.pop();
+                if let Some(link) = self.link.take() {
+                    let index = match self.links.iter().position(|l| *l == link) {
+                        Some(index) => index,
+                        None => {
+                            self.links.push(link);
+                            self.links.len() - 1
+                        }
+                    };
+                    self.push(
+                        format!("[{}]", index + 1),
+                        Style::default().fg(Color::DarkGray),
+                    );
+                }
+            }
+            ("img", _) => {
+                if let Some(alt) = attribute(tag, "alt").filter(|alt| !alt.is_empty()) {
+                    let style = self.style().fg(Color::DarkGray);
+                    let space = if self.line_has_text { " " } else { "" };
+                    self.push(format!("{}[image: {}]", space, alt), style);
+                }
+            }
+            ("ul", false) => {
+                self.end_line();
+                self.lists.push(None);
+            }
+            ("ol", false) => {
+                self.end_line();
+                let start = attribute(tag, "start")
+                    .and_then(|start| start.parse().ok())
+                    .unwrap_or(1);
+                self.lists.push(Some(start));
+            }
+            ("ul" | "ol", true) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.blank_line();
+                } else {
+                    self.end_line();
+                }
+            }
+            ("li", false) => {
+                self.end_line();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let bullet = match self.lists.last_mut() {
+                    Some(Some(num)) => {
+                        *num += 1;
+                        format!("{}{}. ", indent, *num - 1)
+                    }
+                    _ => format!("{}• ", indent),
+                };
+                self.start_line();
+                self.current.push(Span::raw(bullet));
+            }
+            ("li", true) => self.end_line(),
+            ("blockquote", false) => {
+                self.blank_line();
+                self.quote_depth += 1;
+            }
+            ("blockquote", true) => {
+                self.end_line();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+                self.blank_line();
+            }
+            ("table", _) => self.blank_line(),
+            ("tr", true) => self.end_line(),
+            ("th", false) => {
+                self.cell_separator();
+                self.styles
+                    .push(Style::default().add_modifier(Modifier::BOLD));
+            }
+            ("th", true) => {
+                self.styles.pop();
+            }
+            ("td", false) => self.cell_separator(),
+            _ => {}
+        }
+    }
+
+    /// Separates table cells on the same line
+    fn cell_separator(&mut self) {
+        if self.line_has_text {
+            self.push(" | ".to_string(), Style::default().fg(Color::DarkGray));
+        }
+        self.pending_space = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text of each line, without styles
+    fn lines(readme: &str) -> Vec<String> {
+        readme_to_text(readme)
+            .lines
+            .iter()
+            .map(|line| line.0.iter().map(|span| span.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn entities() {
+        assert_eq!(
+            decode_entities("a &amp; b &lt;c&gt; &quot;d&quot; &#39;e&#x27; &#X41;"),
+            "a & b <c> \"d\" 'e' A"
+        );
+        assert_eq!(
+            decode_entities("AT&T &unknown; &#xzz; &"),
+            "AT&T &unknown; &#xzz; &"
+        );
+        assert_eq!(decode_entities("&#1114112;"), "&#1114112;");
+        assert_eq!(decode_entities("&nbsp;&amp;amp;"), " &amp;");
+    }
+
+    #[test]
+    fn nested_lists() {
+        let html = "<ul><li>one<ul><li>two</li></ul></li><li>three</li></ul>\
+                    <ol start=\"3\"><li>a</li><li>b<ol><li>c</li></ol></li></ol>";
+        assert_eq!(
+            lines(html),
+            [
+                "• one",
+                "  • two",
+                "• three",
+                "",
+                "3. a",
+                "4. b",
+                "  1. c",
+                ""
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_tags() {
+        assert_eq!(lines("<p>1 < 2 and 3 > 2</p>"), ["1 < 2 and 3 > 2", ""]);
+        assert_eq!(lines("<p>a <b unclosed"), ["a <b unclosed"]);
+        assert_eq!(lines("<p>text<!-- unclosed comment"), ["text"]);
+    }
+
+    #[test]
+    fn links_are_listed() {
+        assert_eq!(
+            lines("<p>See <a href=\"https://docs.rs?a=1&amp;b=2\">docs</a></p>"),
+            ["See docs[1]", "", "Links", "[1] https://docs.rs?a=1&b=2"]
+        );
+    }
+
+    #[test]
+    fn markdown() {
+        assert_eq!(
+            lines("# Title\n- item\n  * nested\n```\ncode\n```"),
+            ["# Title", "• item", "  • nested", "    code"]
+        );
+    }
+}
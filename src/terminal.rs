@@ -0,0 +1,75 @@
+use std::{
+    error::Error,
+    io::{self, Stdout, Write},
+    panic,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crossterm::{
+    cursor,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use tui::{backend::CrosstermBackend, Terminal};
+
+/// Whether the terminal is in raw mode on the alternate screen, so that output outside of the
+/// TUI, such as printed search results, never gets escape sequences
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Puts the terminal into raw mode on the alternate screen for as long as it lives, and restores
+/// it when dropped, including when an error is returned or a panic unwinds.
+pub struct TerminalGuard {
+    pub terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl TerminalGuard {
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        enable_raw_mode()?;
+        ACTIVE.store(true, Ordering::SeqCst);
+        // from here on the terminal is restored if anything fails
+        let mut stdout = io::stdout();
+        if let Err(err) = execute!(stdout, EnterAlternateScreen, EnableMouseCapture) {
+            restore();
+            return Err(err.into());
+        }
+        match Terminal::new(CrosstermBackend::new(stdout)) {
+            Ok(terminal) => Ok(Self { terminal }),
+            Err(err) => {
+                restore();
+                Err(err.into())
+            }
+        }
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Restores the terminal before the panic message is printed, so that it's readable and the
+/// shell isn't left in raw mode
+pub fn install_panic_hook() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore();
+        hook(info);
+    }));
+}
+
+/// Leaves raw mode, the alternate screen and mouse capture, if the terminal is in them.  Errors
+/// are ignored, since this is the best that can be done while failing.
+fn restore() {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let _ = disable_raw_mode();
+    let _ = execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        cursor::Show
+    );
+}
@@ -0,0 +1,163 @@
+use std::{fmt, str::FromStr, sync::OnceLock};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use tui::style::Color;
+
+/// The theme set from the config when the app starts
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// The colors of the TUI.  Colors that carry a meaning, such as green for an up to date
+/// dependency, aren't themed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Theme {
+    /// The selected item, tab and border, crate names and the labels of the details
+    pub highlight: ThemeColor,
+    /// Table headers and the versions of the search results
+    pub header: ThemeColor,
+    /// Yanked versions and other text of less interest
+    pub muted: ThemeColor,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            highlight: ThemeColor(Color::Red),
+            header: ThemeColor(Color::Blue),
+            muted: ThemeColor(Color::DarkGray),
+        }
+    }
+}
+
+impl Theme {
+    /// Sets the theme used when drawing.  Only the first call has an effect.
+    pub fn init(theme: Theme) {
+        let _ = THEME.set(theme);
+    }
+}
+
+/// The theme used when drawing, the default one until it's set
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
+
+/// A color in the config, either a name such as `light-blue`, an ANSI color index or a hex
+/// color such as `#ff8800`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThemeColor(pub Color);
+
+const COLOR_NAMES: [(&str, Color); 16] = [
+    ("black", Color::Black),
+    ("red", Color::Red),
+    ("green", Color::Green),
+    ("yellow", Color::Yellow),
+    ("blue", Color::Blue),
+    ("magenta", Color::Magenta),
+    ("cyan", Color::Cyan),
+    ("gray", Color::Gray),
+    ("dark-gray", Color::DarkGray),
+    ("light-red", Color::LightRed),
+    ("light-green", Color::LightGreen),
+    ("light-yellow", Color::LightYellow),
+    ("light-blue", Color::LightBlue),
+    ("light-magenta", Color::LightMagenta),
+    ("light-cyan", Color::LightCyan),
+    ("white", Color::White),
+];
+
+impl FromStr for ThemeColor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace(&[' ', '_'][..], "-");
+        if let Some((_, color)) = COLOR_NAMES.iter().find(|(color, _)| *color == name) {
+            return Ok(Self(*color));
+        }
+        if let Some(hex) = name.strip_prefix('#') {
+            let rgb = u32::from_str_radix(hex, 16)
+                .ok()
+                .filter(|_| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()));
+            if let Some(rgb) = rgb {
+                return Ok(Self(Color::Rgb(
+                    (rgb >> 16) as u8,
+                    (rgb >> 8) as u8,
+                    rgb as u8,
+                )));
+            }
+        }
+        if let Ok(index) = name.parse() {
+            return Ok(Self(Color::Indexed(index)));
+        }
+        Err(format!(
+            "Unknown color {}, expected a color index, a hex color such as #ff8800 or one of {}",
+            s,
+            COLOR_NAMES.map(|(name, _)| name).join(", ")
+        ))
+    }
+}
+
+impl fmt::Display for ThemeColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Color::Rgb(r, g, b) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+            Color::Indexed(index) => write!(f, "{}", index),
+            color => {
+                let name = COLOR_NAMES
+                    .iter()
+                    .find(|(_, named)| *named == color)
+                    .map_or("reset", |(name, _)| name);
+                f.write_str(name)
+            }
+        }
+    }
+}
+
+impl Serialize for ThemeColor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ThemeColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn color(s: &str) -> Result<Color, String> {
+        s.parse::<ThemeColor>().map(|color| color.0)
+    }
+
+    #[test]
+    fn parse_colors() {
+        assert_eq!(color("#ff8800"), Ok(Color::Rgb(0xff, 0x88, 0x00)));
+        assert_eq!(color("#FF8800"), Ok(Color::Rgb(0xff, 0x88, 0x00)));
+        assert_eq!(color("12"), Ok(Color::Indexed(12)));
+        assert_eq!(color("light-blue"), Ok(Color::LightBlue));
+        assert_eq!(color("Light Blue"), Ok(Color::LightBlue));
+        assert_eq!(color("dark_gray"), Ok(Color::DarkGray));
+    }
+
+    #[test]
+    fn reject_bad_colors() {
+        assert!(color("#ff88").is_err());
+        assert!(color("#ff88000").is_err());
+        assert!(color("#gg8800").is_err());
+        assert!(color("#+f8800").is_err());
+        assert!(color("256").is_err());
+        assert!(color("orange").is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for s in ["#ff8800", "12", "light-blue", "dark-gray"] {
+            assert_eq!(s.parse::<ThemeColor>().unwrap().to_string(), s);
+        }
+    }
+}
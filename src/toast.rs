@@ -21,7 +21,6 @@ impl ToastMessage {
 }
 
 #[derive(Clone)]
-#[allow(dead_code)]
 pub enum ToastType {
     Info,
     Warning,
@@ -35,23 +34,28 @@ impl StatefulWidget for ToastMessage {
         let title = state.title.as_ref().map_or("", |t| t.as_str());
         let right = area.right();
         let bottom = area.bottom();
-        let len = (25 as u16)
+        // a quarter of the width, but never narrower than 25 unless the terminal is
+        let len = 25u16
             .max(title.len() as u16)
             .max(state.msg.len() as u16)
-            .min(area.width / 4);
+            .min((area.width / 4).max(25))
+            .min(area.width);
 
         let title = Block::default()
             .title(title)
             .borders(Borders::ALL)
             .border_style(Style::default().fg(self.get_color(&state.typ)));
 
+        // tall enough for the wrapped message, with at least three lines
+        let lines = wrapped_lines(&state.msg, len.saturating_sub(2));
+        let height = (lines.max(3) + 2).min(area.height);
         let title_box = Rect {
             x: right - len,
-            y: bottom - 5,
+            y: bottom - height,
             width: len,
-            height: 5,
+            height,
         };
-        (Clear {}).render(title_box.clone(), buf);
+        (Clear {}).render(title_box, buf);
 
         let msg_box = title.inner(title_box);
         title.render(title_box, buf);
@@ -66,6 +70,30 @@ impl StatefulWidget for ToastMessage {
     }
 }
 
+/// How many lines the message takes when its words are wrapped to the width
+fn wrapped_lines(msg: &str, width: u16) -> u16 {
+    let width = width.max(1) as usize;
+    let mut count = 0;
+    for line in msg.lines() {
+        count += 1;
+        let mut used = 0;
+        for word in line.split_whitespace() {
+            let len = word.chars().count();
+            if used > 0 && used + 1 + len > width {
+                count += 1;
+                used = 0;
+            }
+            used += if used > 0 { len + 1 } else { len };
+            // words longer than the width are broken across lines
+            while used > width {
+                count += 1;
+                used -= width;
+            }
+        }
+    }
+    count
+}
+
 pub struct ToastState {
     /// An optional title to display
     title: Option<String>,
@@ -90,10 +118,18 @@ impl ToastState {
         }
     }
 
+    pub fn info<T: Into<String>>(title: Option<T>, msg: T) -> Self {
+        Self::new(title, msg, Duration::from_millis(2500), ToastType::Info)
+    }
+
     pub fn err<T: Into<String>>(title: Option<T>, msg: T) -> Self {
         Self::new(title, msg, Duration::from_millis(2500), ToastType::Error)
     }
 
+    pub fn warn<T: Into<String>>(title: Option<T>, msg: T) -> Self {
+        Self::new(title, msg, Duration::from_millis(2500), ToastType::Warning)
+    }
+
     pub fn start(&mut self) {
         self.end = Some(Instant::now() + self.dur);
     }
@@ -8,29 +8,152 @@ use tui::{
         Direction::{self, Horizontal},
         Layout, Rect,
     },
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans, Text},
     widgets::{
-        Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, StatefulWidget,
-        Widget, Wrap,
+        Block, BorderType, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row,
+        StatefulWidget, Table, TableState, Widget, Wrap,
     },
 };
 
-use crate::{app::SortingField, crates_io::CrateSearch};
+use crate::{
+    advisory::Advisory,
+    app::{HistoryField, SortingField},
+    crates_io::{Category, CrateResponse, CrateSearch, Dependency, Keyword, Owner},
+    format,
+    history::HistoryEntry,
+    manifest::DiffLine,
+    outdated::OutdatedEntry,
+    project::ProjectDep,
+    theme::theme,
+};
 
-pub const STR_FORMAT: &str = "%x %H:%M";
+/// A rectangle of the size centered in the area, shrunk to fit when the area is too small
+fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Shortens the text to the width, ending it with an ellipsis when it doesn't fit
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    match width {
+        0 => String::new(),
+        width => {
+            let mut truncated: String = text.chars().take(width - 1).collect();
+            truncated.push('…');
+            truncated
+        }
+    }
+}
 
 pub struct CrateWidget<'a> {
     crte: &'a CrateSearch,
     selected: bool,
+    marked: bool,
+    project: Option<&'a ProjectDep>,
+    advisories: &'a [Advisory],
+    license: Option<&'a str>,
 }
 
 impl<'a> CrateWidget<'a> {
     pub fn new(crte: &'a CrateSearch, selected: bool) -> Self {
-        Self { crte, selected }
+        Self {
+            crte,
+            selected,
+            marked: false,
+            project: None,
+            advisories: &[],
+            license: None,
+        }
+    }
+
+    /// Shows the crate as marked for comparison
+    pub fn marked(mut self, marked: bool) -> Self {
+        self.marked = marked;
+        self
+    }
+
+    /// Shows a badge when the current project already uses the crate
+    pub fn project(mut self, project: Option<&'a ProjectDep>) -> Self {
+        self.project = project;
+        self
+    }
+
+    /// Shows the license of the crate's newest version
+    pub fn license(mut self, license: Option<&'a str>) -> Self {
+        self.license = license;
+        self
+    }
+
+    /// Shows a badge when the crate has security advisories
+    pub fn advisories(mut self, advisories: &'a [Advisory]) -> Self {
+        self.advisories = advisories;
+        self
+    }
+
+    /// The badges shown after the crate's name
+    fn badges(&self) -> Vec<Span<'static>> {
+        let mut badges = Vec::new();
+        if let Some(dep) = self.project {
+            let usage = if dep.direct { "direct" } else { "transitive" };
+            let badge = match dep.locked_version() {
+                Some(locked) => format!(" {} {} ", usage, locked),
+                None => format!(" {} ", usage),
+            };
+            badges.push(Span::raw(" "));
+            badges.push(Span::styled(
+                badge,
+                Style::default().fg(Color::Black).bg(Color::Green),
+            ));
+
+            if dep.is_outdated(&self.crte.newest_version) {
+                badges.push(Span::raw(" "));
+                badges.push(Span::styled(
+                    format!(" {} available ", self.crte.newest_version),
+                    Style::default().fg(Color::Black).bg(Color::Yellow),
+                ));
+            }
+        }
+
+        let affecting = self
+            .advisories
+            .iter()
+            .filter(|advisory| advisory.affects(&self.crte.newest_version))
+            .collect::<Vec<_>>();
+        if let Some(advisory) = affecting.first() {
+            let badge = match affecting.len() {
+                1 => format!(" {} {} ", advisory.id, advisory.kind()),
+                n => format!(" {} {} +{} more ", advisory.id, advisory.kind(), n - 1),
+            };
+            badges.push(Span::raw(" "));
+            badges.push(Span::styled(
+                badge,
+                Style::default().fg(Color::White).bg(Color::Red),
+            ));
+        } else if !self.advisories.is_empty() {
+            let count = match self.advisories.len() {
+                1 => "1 advisory".to_string(),
+                n => format!("{} advisories", n),
+            };
+            badges.push(Span::styled(
+                format!(" {} for older versions", count),
+                Style::default().fg(Color::Red),
+            ));
+        }
+        badges
     }
 
     fn render_top(&self, area: Rect, buf: &mut Buffer) {
-        let style = Style::default().fg(Color::Red);
+        let style = Style::default().fg(theme().highlight.0);
         let parts = Layout::default()
             .direction(Horizontal)
             .constraints(
@@ -43,31 +166,32 @@ impl<'a> CrateWidget<'a> {
                 .as_ref(),
             )
             .split(area);
-        let paragraph = format!(
-            "Created: {}",
-            self.crte.created_at.format(STR_FORMAT).to_string()
-        );
+        let paragraph = format!("Created: {}", format::date(&self.crte.created_at));
+        let paragraph = truncate(&paragraph, parts[0].width as usize);
         let paragraph = Paragraph::new(paragraph.as_str())
             .style(style)
             .alignment(Left);
         paragraph.render(parts[0], buf);
 
-        let paragraph = format!(
-            "Updated: {}",
-            self.crte.updated_at.format(STR_FORMAT).to_string()
-        );
+        let paragraph = format!("Updated: {}", format::date(&self.crte.updated_at));
+        let paragraph = truncate(&paragraph, parts[1].width as usize);
         let paragraph = Paragraph::new(paragraph.as_str())
             .style(style)
             .alignment(Center);
         paragraph.render(parts[1], buf);
 
-        let paragraph = format!("Downloads: {}", self.crte.downloads);
+        let paragraph = format!("Downloads: {}", format::count(self.crte.downloads));
+        let paragraph = truncate(&paragraph, parts[2].width as usize);
         let paragraph = Paragraph::new(paragraph.as_str())
             .style(style)
             .alignment(Center);
         paragraph.render(parts[2], buf);
 
-        let paragraph = format!("Recent Downloads: {}", self.crte.recent_downloads);
+        let paragraph = format!(
+            "Recent Downloads: {}",
+            format::count(self.crte.recent_downloads)
+        );
+        let paragraph = truncate(&paragraph, parts[3].width as usize);
         let paragraph = Paragraph::new(paragraph.as_str())
             .style(style)
             .alignment(Right);
@@ -75,7 +199,7 @@ impl<'a> CrateWidget<'a> {
     }
 
     fn render_versions(&self, area: Rect, buf: &mut Buffer) {
-        let style = Style::default().fg(Color::Blue);
+        let style = Style::default().fg(theme().header.0);
 
         let sections = Layout::default()
             .direction(Direction::Horizontal)
@@ -91,27 +215,52 @@ impl<'a> CrateWidget<'a> {
             .split(area);
 
         let max_ver = format!("Max Version: {}", self.crte.max_version);
+        let max_ver = truncate(&max_ver, sections[0].width as usize);
         Paragraph::new(max_ver.as_str())
             .style(style)
             .render(sections[0], buf);
 
         let recent_ver = format!("Newest Version: {}", self.crte.newest_version);
+        let recent_ver = truncate(&recent_ver, sections[1].width as usize);
         Paragraph::new(recent_ver.as_str())
             .style(style)
             .alignment(Alignment::Center)
             .render(sections[1], buf);
+
+        // the license can be a long expression, so it gets both of the remaining columns
+        if let Some(license) = self.license {
+            let area = Rect {
+                width: sections[2].width + sections[3].width,
+                ..sections[2]
+            };
+            let license = format!("License: {}", license);
+            let license = truncate(&license, area.width as usize);
+            Paragraph::new(license.as_str())
+                .style(style)
+                .alignment(Right)
+                .render(area, buf);
+        }
     }
 }
 
 impl Widget for CrateWidget<'_> {
     fn render(self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
+        let name = if self.marked {
+            format!("[x] {}", self.crte.name)
+        } else {
+            self.crte.name.clone()
+        };
+        // the badges are cut off by the border before the name is
+        let name = truncate(&name, area.width.saturating_sub(2) as usize);
+        let mut title = vec![Span::raw(name)];
+        title.extend(self.badges());
         let block = Block::default()
             .borders(Borders::ALL)
-            .title(self.crte.name.as_str())
+            .title(Spans::from(title))
             .border_type(BorderType::Plain);
 
         let block = if self.selected {
-            block.border_style(Style::default().fg(Color::Red))
+            block.border_style(Style::default().fg(theme().highlight.0))
         } else {
             block
         };
@@ -142,51 +291,787 @@ impl Widget for CrateWidget<'_> {
     }
 }
 
-pub struct InputWidget<'a, T> {
-    title: T,
-    inpt: &'a str,
+pub struct CrateDetailsWidget<'a> {
+    crte: &'a CrateResponse,
 }
 
-impl<'a, T: AsRef<str>> InputWidget<'a, T> {
-    pub fn new(title: T, inpt: &'a str) -> Self {
-        Self { title, inpt }
+impl<'a> CrateDetailsWidget<'a> {
+    pub fn new(crte: &'a CrateResponse) -> Self {
+        Self { crte }
+    }
+
+    fn render_info(&self, area: Rect, buf: &mut Buffer) {
+        let crte = &self.crte.crte;
+        let newest = self.crte.newest_version();
+        let none = "-".to_string();
+
+        let rows = vec![
+            ("Created", format::date(&crte.created_at)),
+            ("Updated", format::date(&crte.updated_at)),
+            ("Downloads", format::count(crte.downloads)),
+            ("Recent Downloads", format::count(crte.recent_downloads)),
+            ("Max Version", crte.max_version.clone()),
+            ("Newest Version", crte.newest_version.clone()),
+            (
+                "License",
+                newest
+                    .and_then(|version| version.license.clone())
+                    .unwrap_or_else(|| none.clone()),
+            ),
+            (
+                "MSRV",
+                newest
+                    .and_then(|version| version.rust_version.clone())
+                    .unwrap_or_else(|| none.clone()),
+            ),
+            (
+                "Documentation",
+                crte.documentation.clone().unwrap_or_else(|| none.clone()),
+            ),
+            (
+                "Repository",
+                crte.repository.clone().unwrap_or_else(|| none.clone()),
+            ),
+        ];
+
+        let label_style = Style::default().fg(theme().highlight.0);
+        let rows = rows.into_iter().map(|(label, value)| {
+            Row::new(vec![Cell::from(label).style(label_style), value.into()])
+        });
+        let widths = [Constraint::Length(18), Constraint::Percentage(100)];
+        Widget::render(Table::new(rows).widths(&widths), area, buf);
+    }
+
+    fn render_versions(&self, area: Rect, buf: &mut Buffer) {
+        let header = Row::new(vec!["Version", "Published", "Downloads", "License", ""])
+            .style(Style::default().fg(theme().header.0));
+        let rows = self.crte.versions.iter().map(|version| {
+            let row = Row::new(vec![
+                version.num.clone(),
+                format::date(&version.created_at),
+                format::count(version.downloads),
+                version.license.clone().unwrap_or_default(),
+                if version.yanked { "yanked" } else { "" }.to_string(),
+            ]);
+            if version.yanked {
+                row.style(Style::default().fg(theme().muted.0))
+            } else {
+                row
+            }
+        });
+        let widths = [
+            Constraint::Percentage(20),
+            Constraint::Percentage(25),
+            Constraint::Percentage(15),
+            Constraint::Percentage(30),
+            Constraint::Percentage(10),
+        ];
+        let block = Block::default().borders(Borders::TOP).title("Versions");
+        Widget::render(
+            Table::new(rows).header(header).widths(&widths).block(block),
+            area,
+            buf,
+        );
     }
 }
-impl<'a, T: AsRef<str>> InputWidget<'a, T> {
-    fn get_area(&self, area: Rect) -> Rect {
-        let len = self.inpt.len() + 5; // the length needed + some padding + 1 for the '|' character
-        let len = len.max(25).max(self.title.as_ref().len()) as u16;
 
-        let horz_pad = (area.width - len).wrapping_div(2);
+impl Widget for CrateDetailsWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.crte.crte.name.as_str())
+            .border_type(BorderType::Plain)
+            .border_style(Style::default().fg(theme().highlight.0));
+        let inner = block.inner(area);
+        block.render(area, buf);
 
-        let center = Layout::default()
-            .direction(Direction::Horizontal)
+        let sections = Layout::default()
+            .horizontal_margin(1)
             .constraints(
                 [
-                    Constraint::Length(horz_pad),
-                    Constraint::Min(len + 2),
-                    Constraint::Length(horz_pad),
+                    Constraint::Length(3),
+                    Constraint::Length(10),
+                    Constraint::Min(0),
                 ]
                 .as_ref(),
             )
-            .split(area)[1];
+            .split(inner);
 
-        let height = 5;
-        let vert_pad = (area.height - height) / 2;
+        if let Some(ref desc) = self.crte.crte.description {
+            Paragraph::new(desc.as_str())
+                .wrap(Wrap { trim: true })
+                .render(sections[0], buf);
+        }
+        self.render_info(sections[1], buf);
+        self.render_versions(sections[2], buf);
+    }
+}
 
-        let center = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(
-                [
-                    Constraint::Length(vert_pad),
-                    Constraint::Min(height),
-                    Constraint::Length(vert_pad),
-                ]
-                .as_ref(),
+pub struct DependenciesWidget<'a> {
+    deps: &'a [Dependency],
+    selection: Option<usize>,
+}
+
+impl<'a> DependenciesWidget<'a> {
+    pub fn new(deps: &'a [Dependency], selection: Option<usize>) -> Self {
+        Self { deps, selection }
+    }
+}
+
+impl Widget for DependenciesWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let header = Row::new(vec![
+            "Name",
+            "Requirement",
+            "Kind",
+            "Optional",
+            "Target",
+            "Features",
+        ])
+        .style(Style::default().fg(theme().header.0));
+        let rows = self.deps.iter().map(|dep| {
+            Row::new(vec![
+                dep.crate_id.clone(),
+                dep.req.clone(),
+                dep.kind.clone(),
+                if dep.optional { "yes" } else { "" }.to_string(),
+                dep.target.clone().unwrap_or_default(),
+                dep.features.join(", "),
+            ])
+        });
+        let widths = [
+            Constraint::Percentage(25),
+            Constraint::Percentage(15),
+            Constraint::Percentage(10),
+            Constraint::Percentage(10),
+            Constraint::Percentage(15),
+            Constraint::Percentage(25),
+        ];
+        let table = Table::new(rows)
+            .header(header)
+            .widths(&widths)
+            .block(Block::default().borders(Borders::ALL).title("Dependencies"))
+            .highlight_style(
+                Style::default()
+                    .fg(theme().highlight.0)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("* ");
+
+        let mut state = TableState::default();
+        state.select(self.selection);
+        StatefulWidget::render(table, area, buf, &mut state);
+    }
+}
+
+pub struct VersionsWidget<'a> {
+    crte: &'a CrateResponse,
+    selection: Option<usize>,
+    advisories: &'a [Advisory],
+}
+
+impl<'a> VersionsWidget<'a> {
+    pub fn new(crte: &'a CrateResponse, selection: Option<usize>) -> Self {
+        Self {
+            crte,
+            selection,
+            advisories: &[],
+        }
+    }
+
+    /// Marks the versions affected by the advisories and lists them below the versions
+    pub fn advisories(mut self, advisories: &'a [Advisory]) -> Self {
+        self.advisories = advisories;
+        self
+    }
+
+    fn render_advisories(&self, area: Rect, buf: &mut Buffer) {
+        let header = Row::new(vec!["ID", "Severity", "Title", "Patched"])
+            .style(Style::default().fg(theme().header.0));
+        let rows = self.advisories.iter().map(|advisory| {
+            let patched = if advisory.patched.is_empty() {
+                "none".to_string()
+            } else {
+                advisory.patched.join(", ")
+            };
+            Row::new(vec![
+                advisory.id.clone(),
+                advisory.kind(),
+                advisory.title.clone(),
+                patched,
+            ])
+        });
+        let widths = [
+            Constraint::Percentage(16),
+            Constraint::Percentage(12),
+            Constraint::Percentage(47),
+            Constraint::Percentage(25),
+        ];
+        Widget::render(
+            Table::new(rows)
+                .header(header)
+                .widths(&widths)
+                .style(Style::default().fg(Color::Red))
+                .block(Block::default().borders(Borders::ALL).title("Advisories")),
+            area,
+            buf,
+        );
+    }
+}
+
+impl Widget for VersionsWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = if self.advisories.is_empty() {
+            area
+        } else {
+            let height = cmp::min(self.advisories.len() as u16 + 3, area.height / 3);
+            let sections = Layout::default()
+                .constraints([Constraint::Min(0), Constraint::Length(height)].as_ref())
+                .split(area);
+            self.render_advisories(sections[1], buf);
+            sections[0]
+        };
+
+        let header = Row::new(vec![
+            "Version",
+            "Published",
+            "Downloads",
+            "License",
+            "MSRV",
+            "Size",
+            "",
+        ])
+        .style(Style::default().fg(theme().header.0));
+        let rows = self.crte.versions.iter().map(|version| {
+            let affecting = self
+                .advisories
+                .iter()
+                .filter(|advisory| advisory.affects(&version.num))
+                .map(|advisory| advisory.id.as_str())
+                .collect::<Vec<_>>();
+            let mut flags = affecting.join(" ");
+            if version.yanked {
+                flags.insert_str(
+                    0,
+                    if flags.is_empty() {
+                        "yanked"
+                    } else {
+                        "yanked "
+                    },
+                );
+            }
+            let row = Row::new(vec![
+                version.num.clone(),
+                format::date(&version.created_at),
+                format::count(version.downloads),
+                version.license.clone().unwrap_or_default(),
+                version.rust_version.clone().unwrap_or_default(),
+                version
+                    .crate_size
+                    .map(|size| format!("{:.1} KiB", size as f64 / 1024.0))
+                    .unwrap_or_default(),
+                flags,
+            ]);
+            if !affecting.is_empty() {
+                row.style(Style::default().fg(Color::Red))
+            } else if version.yanked {
+                row.style(Style::default().fg(theme().muted.0))
+            } else {
+                row
+            }
+        });
+        let widths = [
+            Constraint::Percentage(12),
+            Constraint::Percentage(16),
+            Constraint::Percentage(10),
+            Constraint::Percentage(20),
+            Constraint::Percentage(6),
+            Constraint::Percentage(10),
+            Constraint::Percentage(26),
+        ];
+        let title = format!("{} > Versions", self.crte.crte.name);
+        let table = Table::new(rows)
+            .header(header)
+            .widths(&widths)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(
+                Style::default()
+                    .fg(theme().highlight.0)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("* ");
+
+        let mut state = TableState::default();
+        state.select(self.selection);
+        StatefulWidget::render(table, area, buf, &mut state);
+    }
+}
+
+pub struct OutdatedWidget<'a> {
+    entries: &'a [OutdatedEntry],
+    selection: Option<usize>,
+}
+
+impl<'a> OutdatedWidget<'a> {
+    pub fn new(entries: &'a [OutdatedEntry], selection: Option<usize>) -> Self {
+        Self { entries, selection }
+    }
+}
+
+impl Widget for OutdatedWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let header = Row::new(vec![
+            "Name",
+            "Requirement",
+            "Locked",
+            "Compatible",
+            "Latest",
+            "Status",
+        ])
+        .style(Style::default().fg(theme().header.0));
+        let version = |version: &Option<semver::Version>| {
+            version
+                .as_ref()
+                .map_or_else(|| "-".to_string(), ToString::to_string)
+        };
+        let rows = self.entries.iter().map(|entry| {
+            let status_style = match entry.status() {
+                "yanked" => Style::default().fg(Color::Red),
+                "outdated" => Style::default().fg(Color::Yellow),
+                "up to date" => Style::default().fg(Color::Green),
+                _ => Style::default().fg(Color::DarkGray),
+            };
+            Row::new(vec![
+                Cell::from(entry.name.clone()),
+                Cell::from(entry.req.clone().unwrap_or_else(|| "-".to_string())),
+                Cell::from(version(&entry.locked)),
+                Cell::from(version(&entry.compatible)),
+                Cell::from(version(&entry.latest)),
+                Cell::from(entry.status()).style(status_style),
+            ])
+        });
+        let widths = [
+            Constraint::Percentage(25),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+        ];
+        let table = Table::new(rows)
+            .header(header)
+            .widths(&widths)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Outdated Dependencies"),
+            )
+            .highlight_style(
+                Style::default()
+                    .fg(theme().highlight.0)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("* ");
+
+        let mut state = TableState::default();
+        state.select(self.selection);
+        StatefulWidget::render(table, area, buf, &mut state);
+    }
+}
+
+pub struct OwnersWidget<'a> {
+    owners: &'a [Owner],
+    selection: Option<usize>,
+}
+
+impl<'a> OwnersWidget<'a> {
+    pub fn new(owners: &'a [Owner], selection: Option<usize>) -> Self {
+        Self { owners, selection }
+    }
+}
+
+impl Widget for OwnersWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let header = Row::new(vec!["Login", "Name", "Kind", "URL"])
+            .style(Style::default().fg(theme().header.0));
+        let rows = self.owners.iter().map(|owner| {
+            Row::new(vec![
+                owner.login.clone(),
+                owner.name.clone().unwrap_or_default(),
+                owner.kind.clone(),
+                owner.url.clone().unwrap_or_default(),
+            ])
+        });
+        let widths = [
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(10),
+            Constraint::Percentage(40),
+        ];
+        let table = Table::new(rows)
+            .header(header)
+            .widths(&widths)
+            .block(Block::default().borders(Borders::ALL).title("Owners"))
+            .highlight_style(
+                Style::default()
+                    .fg(theme().highlight.0)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("* ");
+
+        let mut state = TableState::default();
+        state.select(self.selection);
+        StatefulWidget::render(table, area, buf, &mut state);
+    }
+}
+
+/// A category in the list of categories.  Subcategories follow their parent once it is expanded.
+pub struct CategoryRow<'a> {
+    pub category: &'a Category,
+    pub subcategory: bool,
+    pub expanded: bool,
+}
+
+pub struct CategoriesWidget<'a> {
+    rows: &'a [CategoryRow<'a>],
+    selection: Option<usize>,
+}
+
+impl<'a> CategoriesWidget<'a> {
+    pub fn new(rows: &'a [CategoryRow<'a>], selection: Option<usize>) -> Self {
+        Self { rows, selection }
+    }
+}
+
+impl Widget for CategoriesWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let header = Row::new(vec!["Category", "Crates", "Description"])
+            .style(Style::default().fg(theme().header.0));
+        let rows = self.rows.iter().map(|row| {
+            let name = if row.subcategory {
+                // subcategories are named after their parent, e.g. `Web programming::HTTP client`
+                let name = row
+                    .category
+                    .category
+                    .rsplit("::")
+                    .next()
+                    .unwrap_or_default();
+                format!("    {}", name)
+            } else if row.expanded {
+                format!("- {}", row.category.category)
+            } else {
+                format!("+ {}", row.category.category)
+            };
+            Row::new(vec![
+                name,
+                format::count(row.category.crates_cnt),
+                row.category.description.clone(),
+            ])
+        });
+        let widths = [
+            Constraint::Percentage(30),
+            Constraint::Percentage(10),
+            Constraint::Percentage(60),
+        ];
+        let table = Table::new(rows)
+            .header(header)
+            .widths(&widths)
+            .block(Block::default().borders(Borders::ALL).title("Categories"))
+            .highlight_style(
+                Style::default()
+                    .fg(theme().highlight.0)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("* ");
+
+        let mut state = TableState::default();
+        state.select(self.selection);
+        StatefulWidget::render(table, area, buf, &mut state);
+    }
+}
+
+pub struct KeywordsWidget<'a> {
+    keywords: &'a [Keyword],
+    selection: Option<usize>,
+}
+
+impl<'a> KeywordsWidget<'a> {
+    pub fn new(keywords: &'a [Keyword], selection: Option<usize>) -> Self {
+        Self {
+            keywords,
+            selection,
+        }
+    }
+}
+
+impl Widget for KeywordsWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let header =
+            Row::new(vec!["Keyword", "Crates"]).style(Style::default().fg(theme().header.0));
+        let rows = self.keywords.iter().map(|keyword| {
+            Row::new(vec![
+                keyword.keyword.clone(),
+                format::count(keyword.crates_cnt),
+            ])
+        });
+        let widths = [Constraint::Percentage(50), Constraint::Percentage(50)];
+        let table = Table::new(rows)
+            .header(header)
+            .widths(&widths)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Popular Keywords"),
             )
-            .split(center)[1];
+            .highlight_style(
+                Style::default()
+                    .fg(theme().highlight.0)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("* ");
+
+        let mut state = TableState::default();
+        state.select(self.selection);
+        StatefulWidget::render(table, area, buf, &mut state);
+    }
+}
+
+/// The data for one crate in the comparison table
+pub struct CompareColumn<'a> {
+    pub crte: &'a CrateResponse,
+    pub owners: Option<&'a [Owner]>,
+    pub deps: Option<&'a [Dependency]>,
+}
+
+impl CompareColumn<'_> {
+    /// The MSRV of the newest version as (major, minor, patch)
+    fn msrv(&self) -> Option<(u32, u32, u32)> {
+        let msrv = self.crte.newest_version()?.rust_version.as_ref()?;
+        let mut parts = msrv.split('.').map(|part| part.parse::<u32>().ok());
+        Some((
+            parts.next()??,
+            parts.next().flatten().unwrap_or(0),
+            parts.next().flatten().unwrap_or(0),
+        ))
+    }
+
+    /// The number of normal (non dev or build) dependencies
+    fn dep_count(&self) -> Option<usize> {
+        self.deps
+            .map(|deps| deps.iter().filter(|dep| dep.kind == "normal").count())
+    }
+}
+
+/// A row of the comparison table: the label, how to display each crate's value and, optionally,
+/// a score used to highlight the best value (higher is better)
+type CompareRow = (
+    &'static str,
+    fn(&CompareColumn) -> String,
+    Option<fn(&CompareColumn) -> Option<i64>>,
+);
+
+pub struct CompareWidget<'a> {
+    columns: Vec<CompareColumn<'a>>,
+}
+
+impl<'a> CompareWidget<'a> {
+    pub fn new(columns: Vec<CompareColumn<'a>>) -> Self {
+        Self { columns }
+    }
+
+    fn rows() -> Vec<CompareRow> {
+        vec![
+            (
+                "Downloads",
+                |col| format::count(col.crte.crte.downloads),
+                Some(|col| Some(col.crte.crte.downloads as i64)),
+            ),
+            (
+                "Recent Downloads",
+                |col| format::count(col.crte.crte.recent_downloads),
+                Some(|col| Some(col.crte.crte.recent_downloads as i64)),
+            ),
+            (
+                "Created",
+                |col| format::date(&col.crte.crte.created_at),
+                // older crates have had more time to mature
+                Some(|col| Some(-col.crte.crte.created_at.timestamp())),
+            ),
+            (
+                "Updated",
+                |col| format::date(&col.crte.crte.updated_at),
+                Some(|col| Some(col.crte.crte.updated_at.timestamp())),
+            ),
+            (
+                "Latest Version",
+                |col| col.crte.crte.newest_version.clone(),
+                None,
+            ),
+            (
+                "License",
+                |col| {
+                    col.crte
+                        .newest_version()
+                        .and_then(|version| version.license.clone())
+                        .unwrap_or_else(|| "-".to_string())
+                },
+                None,
+            ),
+            (
+                "MSRV",
+                |col| {
+                    col.crte
+                        .newest_version()
+                        .and_then(|version| version.rust_version.clone())
+                        .unwrap_or_else(|| "-".to_string())
+                },
+                Some(|col| {
+                    col.msrv().map(|(major, minor, patch)| {
+                        -((major * 1_000_000 + minor * 1000 + patch) as i64)
+                    })
+                }),
+            ),
+            (
+                "Dependencies",
+                |col| match (col.dep_count(), col.deps) {
+                    (Some(count), Some(deps)) => {
+                        format!("{} (+{} dev/build)", count, deps.len() - count)
+                    }
+                    _ => "-".to_string(),
+                },
+                Some(|col| col.dep_count().map(|count| -(count as i64))),
+            ),
+            (
+                "Owners",
+                |col| {
+                    col.owners.map_or_else(
+                        || "-".to_string(),
+                        |owners| {
+                            owners
+                                .iter()
+                                .map(|owner| owner.login.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        },
+                    )
+                },
+                Some(|col| col.owners.map(|owners| owners.len() as i64)),
+            ),
+            (
+                "Repository",
+                |col| {
+                    col.crte
+                        .crte
+                        .repository
+                        .clone()
+                        .unwrap_or_else(|| "-".to_string())
+                },
+                None,
+            ),
+        ]
+    }
+}
+
+impl Widget for CompareWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let label_style = Style::default().fg(theme().highlight.0);
+        let best_style = Style::default()
+            .fg(Color::Green)
+            .add_modifier(Modifier::BOLD);
+
+        let mut header = vec![Cell::from("")];
+        header.extend(
+            self.columns
+                .iter()
+                .map(|col| Cell::from(col.crte.crte.name.as_str())),
+        );
+        let header = Row::new(header).style(Style::default().fg(theme().header.0));
+
+        let rows = Self::rows().into_iter().map(|(label, value, score)| {
+            let scores = score.map(|score| self.columns.iter().map(score).collect::<Vec<_>>());
+            // only highlight when the values actually differ
+            let best = scores.as_ref().and_then(|scores| {
+                let max = scores.iter().flatten().max()?;
+                let min = scores.iter().flatten().min()?;
+                if max != min {
+                    Some(*max)
+                } else {
+                    None
+                }
+            });
+
+            let mut cells = vec![Cell::from(label).style(label_style)];
+            for (i, col) in self.columns.iter().enumerate() {
+                let cell = Cell::from(value(col));
+                let is_best =
+                    best.is_some() && scores.as_ref().and_then(|scores| scores[i]) == best;
+                cells.push(if is_best {
+                    cell.style(best_style)
+                } else {
+                    cell
+                });
+            }
+            Row::new(cells)
+        });
+
+        // split what is left after the labels, borders and column spacing between the crates
+        let num_cols = self.columns.len().max(1) as u16;
+        let col_width = area.width.saturating_sub(20 + 2 * num_cols) / num_cols;
+        let mut widths = vec![Constraint::Length(18)];
+        widths.extend(self.columns.iter().map(|_| Constraint::Length(col_width)));
+        let table = Table::new(rows)
+            .header(header)
+            .widths(&widths)
+            .column_spacing(2)
+            .block(Block::default().borders(Borders::ALL).title("Compare"));
+        Widget::render(table, area, buf);
+    }
+}
+
+pub struct ReadmeWidget<'a> {
+    text: Text<'a>,
+    title: &'a str,
+    /// The first line to show
+    scroll: usize,
+}
+
+impl<'a> ReadmeWidget<'a> {
+    pub fn new(text: Text<'a>, title: &'a str, scroll: usize) -> Self {
+        Self {
+            text,
+            title,
+            scroll,
+        }
+    }
+}
+
+impl Widget for ReadmeWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default().borders(Borders::ALL).title(self.title);
+        Paragraph::new(self.text)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll.min(u16::MAX as usize) as u16, 0))
+            .render(area, buf);
+    }
+}
+
+pub struct InputWidget<'a, T> {
+    title: T,
+    inpt: &'a str,
+}
+
+impl<'a, T: AsRef<str>> InputWidget<'a, T> {
+    pub fn new(title: T, inpt: &'a str) -> Self {
+        Self { title, inpt }
+    }
+}
+impl<'a, T: AsRef<str>> InputWidget<'a, T> {
+    fn get_area(&self, area: Rect) -> Rect {
+        let len = self.inpt.len() + 5; // the length needed + some padding + 1 for the '|' character
+        let len = len.max(25).max(self.title.as_ref().len()) as u16;
 
-        center
+        centered_rect(area, len + 2, 5)
     }
 }
 
@@ -232,38 +1117,10 @@ impl<'a> SortingWidget<'a> {
         } = self.state;
 
         let height = strs.len() as u16 + 2;
-        let len = strs.iter().map(String::len).max().unwrap();
+        let len = strs.iter().map(String::len).max().unwrap_or(0);
         let len = cmp::max(len, self.title.len()) as u16 + 4;
 
-        let horz_pad = (area.width - len as u16).wrapping_div(2);
-
-        let center = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints(
-                [
-                    Constraint::Length(horz_pad),
-                    Constraint::Min(len),
-                    Constraint::Length(horz_pad),
-                ]
-                .as_ref(),
-            )
-            .split(area)[1];
-
-        let vert_pad = (area.height - height) / 2;
-
-        let center = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(
-                [
-                    Constraint::Length(vert_pad),
-                    Constraint::Min(height),
-                    Constraint::Length(vert_pad),
-                ]
-                .as_ref(),
-            )
-            .split(center)[1];
-
-        center
+        centered_rect(area, len, height)
     }
 }
 
@@ -288,3 +1145,196 @@ impl<'a> Widget for SortingWidget<'a> {
         StatefulWidget::render(list, inner, buf, &mut state);
     }
 }
+
+/// The maximum number of history entries shown at once
+const HISTORY_ROWS: usize = 10;
+
+pub struct HistoryWidget<'a> {
+    state: &'a HistoryField,
+    entries: Vec<&'a HistoryEntry>,
+    title: &'a str,
+}
+
+impl<'a> HistoryWidget<'a> {
+    pub fn new(state: &'a HistoryField, entries: Vec<&'a HistoryEntry>, title: &'a str) -> Self {
+        Self {
+            state,
+            entries,
+            title,
+        }
+    }
+
+    fn entry_str(entry: &HistoryEntry) -> String {
+        format!("{}  ({})", entry.term, entry.sort)
+    }
+
+    fn get_area(&self, area: Rect) -> Rect {
+        let len = self
+            .entries
+            .iter()
+            .map(|entry| Self::entry_str(entry).len())
+            .max()
+            .unwrap_or(0)
+            .max(self.state.filter.len() + 3)
+            .max(self.title.len())
+            .max(30) as u16
+            + 4;
+        let height = self.entries.len().clamp(1, HISTORY_ROWS) as u16 + 3;
+
+        centered_rect(area, len, height)
+    }
+}
+
+impl<'a> Widget for HistoryWidget<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = self.get_area(area);
+        Clear.render(area, buf);
+        let border = Block::default().borders(Borders::ALL).title(self.title);
+        let inner = border.inner(area);
+        border.render(area, buf);
+
+        let sections = Layout::default()
+            .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+            .split(inner);
+
+        Paragraph::new(format!("> {}|", self.state.filter)).render(sections[0], buf);
+
+        if self.entries.is_empty() {
+            Paragraph::new("No matching searches")
+                .style(Style::default().fg(theme().muted.0))
+                .render(sections[1], buf);
+            return;
+        }
+
+        let mut state = ListState::default();
+        state.select(Some(self.state.selection));
+        let items = self
+            .entries
+            .iter()
+            .map(|entry| ListItem::new(Self::entry_str(entry)))
+            .collect::<Vec<_>>();
+        let list = List::new(items)
+            .highlight_symbol("* ")
+            .highlight_style(Style::default().fg(theme().highlight.0));
+
+        StatefulWidget::render(list, sections[1], buf, &mut state);
+    }
+}
+
+pub struct MenuWidget<'a> {
+    items: Vec<String>,
+    selection: usize,
+    title: &'a str,
+}
+
+impl<'a> MenuWidget<'a> {
+    pub fn new(items: Vec<String>, selection: usize, title: &'a str) -> Self {
+        Self {
+            items,
+            selection,
+            title,
+        }
+    }
+
+    fn get_area(&self, area: Rect) -> Rect {
+        let len = self
+            .items
+            .iter()
+            .map(String::len)
+            .max()
+            .unwrap_or(0)
+            .max(self.title.len()) as u16
+            + 6;
+
+        centered_rect(area, len, self.items.len() as u16 + 2)
+    }
+}
+
+impl<'a> Widget for MenuWidget<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = self.get_area(area);
+        Clear.render(area, buf);
+        let border = Block::default().borders(Borders::ALL).title(self.title);
+        let inner = border.inner(area);
+        border.render(area, buf);
+
+        let mut state = ListState::default();
+        state.select(Some(self.selection));
+        let items = self
+            .items
+            .into_iter()
+            .map(ListItem::new)
+            .collect::<Vec<_>>();
+        let list = List::new(items)
+            .highlight_symbol("* ")
+            .highlight_style(Style::default().fg(theme().highlight.0));
+
+        StatefulWidget::render(list, inner, buf, &mut state);
+    }
+}
+
+/// A popup previewing the changes to a file
+pub struct DiffWidget<'a> {
+    lines: &'a [DiffLine],
+    title: &'a str,
+    /// The first line to show
+    scroll: usize,
+}
+
+impl<'a> DiffWidget<'a> {
+    pub fn new(lines: &'a [DiffLine], title: &'a str, scroll: usize) -> Self {
+        Self {
+            lines,
+            title,
+            scroll,
+        }
+    }
+
+    fn get_area(&self, area: Rect) -> Rect {
+        let width = (area.width * 4 / 5).max(40);
+        let height = (self.lines.len() as u16 + 2)
+            .min(area.height * 4 / 5)
+            .max(5);
+
+        centered_rect(area, width, height)
+    }
+}
+
+impl Widget for DiffWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = self.get_area(area);
+        Clear.render(area, buf);
+
+        let mut lines = self
+            .lines
+            .iter()
+            .map(|line| match line {
+                DiffLine::Same(text) => Spans::from(format!("  {}", text)),
+                DiffLine::Added(text) => Spans::from(Span::styled(
+                    format!("+ {}", text),
+                    Style::default().fg(Color::Green),
+                )),
+                DiffLine::Removed(text) => Spans::from(Span::styled(
+                    format!("- {}", text),
+                    Style::default().fg(Color::Red),
+                )),
+                DiffLine::Skipped => Spans::from(Span::styled(
+                    "  ...",
+                    Style::default().add_modifier(Modifier::DIM),
+                )),
+            })
+            .collect::<Vec<_>>();
+        if lines.is_empty() {
+            lines.push(Spans::from(Span::styled(
+                "  No changes",
+                Style::default().add_modifier(Modifier::DIM),
+            )));
+        }
+
+        let block = Block::default().borders(Borders::ALL).title(self.title);
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((self.scroll.min(u16::MAX as usize) as u16, 0))
+            .render(area, buf);
+    }
+}